@@ -0,0 +1,57 @@
+//! Headless server for online matches.
+//!
+//! Usage: `server [address] [number of players] [spectator delay in seconds]`
+
+use std::time::Duration;
+
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin, prelude::*};
+use bevy_cartel::net::{server::ServerPlugin, DEFAULT_ADDRESS, MAX_PLAYERS, MIN_PLAYERS};
+
+const USAGE: &str = "Usage: server [address] [number of players] [spectator delay in seconds]";
+
+fn main() -> AppExit {
+    let mut args = std::env::args().skip(1);
+    let address = args.next().unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let num_players = match args.next().map(|num_players| num_players.parse::<usize>()) {
+        None => MIN_PLAYERS,
+        Some(Ok(num_players)) if (MIN_PLAYERS..=MAX_PLAYERS).contains(&num_players) => num_players,
+        Some(_) => {
+            eprintln!(
+                "The number of players must be between {} and {}.\n{}",
+                MIN_PLAYERS, MAX_PLAYERS, USAGE
+            );
+            return AppExit::error();
+        }
+    };
+    let spectator_delay = match args.next() {
+        None => Duration::ZERO,
+        Some(seconds) => match seconds
+            .parse()
+            .ok()
+            .and_then(|seconds| Duration::try_from_secs_f32(seconds).ok())
+        {
+            Some(spectator_delay) => spectator_delay,
+            None => {
+                eprintln!(
+                    "The spectator delay must be a number of seconds, 0 or more.\n{}",
+                    USAGE
+                );
+                return AppExit::error();
+            }
+        },
+    };
+
+    App::new()
+        .add_plugins((
+            MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                1.0 / 60.0,
+            ))),
+            LogPlugin::default(),
+            ServerPlugin {
+                address,
+                num_players,
+                spectator_delay,
+            },
+        ))
+        .run()
+}
@@ -0,0 +1,119 @@
+//! A simple computer opponent for seats marked as AI on the setup screen.
+//!
+//! The AI drives the same events a human triggers through the UI, so every
+//! rule and animation applies to it unchanged.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{
+    events::{CardPress, DrawHand},
+    Card, CardOnTable, Chip, ChipArea, Hand, LaMesaPluginSettings,
+};
+
+use super::{
+    cards::{
+        AdvancePhase, CardType, ChipType, EffectType, GameState, Kard, TurnPhase, LOAN_AMOUNT,
+        TABLE_SLOTS,
+    },
+    settings::MatchSettings,
+};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(AiTimer(Timer::from_seconds(0.8, TimerMode::Repeating)))
+        .add_systems(Update, play_ai_turn.run_if(in_state(Screen::Playing)));
+}
+
+/// Delay between two AI actions so that players can follow what happens.
+#[derive(Resource)]
+pub struct AiTimer(pub Timer);
+
+fn play_ai_turn(
+    time: Res<Time>,
+    mut timer: ResMut<AiTimer>,
+    match_settings: Res<MatchSettings>,
+    plugin_settings: Res<LaMesaPluginSettings>,
+    mut state: ResMut<GameState>,
+    cards_in_hand: Query<(Entity, &Card<Kard>, &Hand)>,
+    cards_on_table: Query<&CardOnTable>,
+    chips: Query<&ChipArea, With<Chip<ChipType>>>,
+    mut ew_draw: EventWriter<DrawHand>,
+    mut ew_card_press: EventWriter<CardPress>,
+    mut ew_advance_phase: EventWriter<AdvancePhase>,
+) {
+    let player = state.player;
+    if !match_settings.is_ai(player) {
+        return;
+    }
+
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    match state.phase {
+        TurnPhase::Prepare => {
+            // Borrow to keep producing, pay back once business is good.
+            if state.get_balance(player) < LOAN_AMOUNT {
+                state.borrow(player);
+            } else if state.get_balance(player) > 5 * LOAN_AMOUNT {
+                state.repay(player);
+            }
+
+            ew_draw.send(DrawHand {
+                deck_marker: state.playing_deck(player),
+                num_cards: plugin_settings.hand_size,
+                player,
+            });
+            ew_advance_phase.send(AdvancePhase);
+        }
+        TurnPhase::PlaceCardsOnTable => {
+            let cards_played = cards_on_table
+                .iter()
+                .filter(|card_on_table| card_on_table.player == player)
+                .count();
+            let chips_in_area = |marker: usize| {
+                chips
+                    .iter()
+                    .filter(|area| area.player == player && area.marker == marker)
+                    .count()
+            };
+            let produced = chips_in_area(1);
+            let ready_for_sale = chips_in_area(2);
+
+            let best_card = cards_in_hand
+                .iter()
+                .filter(|(_, card, hand)| {
+                    hand.player == player && card.data.price <= state.get_balance(player)
+                })
+                .filter_map(|(entity, card, _)| {
+                    let priority = match card.data.card_type {
+                        CardType::Export if state.has_effect(player, EffectType::BorderClosure) => {
+                            return None
+                        }
+                        CardType::Export | CardType::LocalMarket if ready_for_sale > 0 => 4,
+                        CardType::Truck | CardType::Train if produced > 0 => 3,
+                        CardType::Warehouse
+                            if produced + 10 > state.storage_capacity(player, 1) =>
+                        {
+                            3
+                        }
+                        CardType::Cocaine | CardType::Cannabis => 2,
+                        // No way to tell what a script is worth.
+                        CardType::Attack | CardType::Scripted => 1,
+                        _ => return None,
+                    };
+                    Some((priority, entity))
+                })
+                .max_by_key(|(priority, _)| *priority);
+
+            match best_card {
+                Some((_, card_entity)) if cards_played < TABLE_SLOTS => {
+                    ew_card_press.send(CardPress { card_entity });
+                }
+                _ => {
+                    ew_advance_phase.send(AdvancePhase);
+                }
+            }
+        }
+        _ => {}
+    }
+}
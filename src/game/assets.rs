@@ -3,6 +3,7 @@ use bevy::{
     render::texture::{ImageLoaderSettings, ImageSampler},
     utils::HashMap,
 };
+use serde::Deserialize;
 
 pub(super) fn plugin(app: &mut App) {
     app.register_type::<HandleMap<ImageKey>>();
@@ -43,7 +44,7 @@ impl FromWorld for HandleMap<ImageKey> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Reflect, Deserialize)]
 pub enum ChipModel {
     Cocaine,
     Cannabis,
@@ -55,7 +56,13 @@ impl AssetKey for ChipModel {
 
 impl FromWorld for HandleMap<ChipModel> {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
+        Self::with_defaults(world.resource::<AssetServer>())
+    }
+}
+
+impl HandleMap<ChipModel> {
+    /// The models shipped with the game, before mods replace any of them.
+    pub fn with_defaults(asset_server: &AssetServer) -> Self {
         [
             (
                 ChipModel::Cannabis,
@@ -72,7 +79,7 @@ impl FromWorld for HandleMap<ChipModel> {
     }
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Reflect)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Reflect, Deserialize)]
 pub enum SfxKey {
     ButtonHover,
     ButtonPress,
@@ -80,6 +87,7 @@ pub enum SfxKey {
     Step2,
     Step3,
     Step4,
+    ChipSale,
 }
 
 impl AssetKey for SfxKey {
@@ -88,7 +96,13 @@ impl AssetKey for SfxKey {
 
 impl FromWorld for HandleMap<SfxKey> {
     fn from_world(world: &mut World) -> Self {
-        let asset_server = world.resource::<AssetServer>();
+        Self::with_defaults(world.resource::<AssetServer>())
+    }
+}
+
+impl HandleMap<SfxKey> {
+    /// The sounds shipped with the game, before mods replace any of them.
+    pub fn with_defaults(asset_server: &AssetServer) -> Self {
         [
             (
                 SfxKey::ButtonHover,
@@ -102,6 +116,11 @@ impl FromWorld for HandleMap<SfxKey> {
             (SfxKey::Step2, asset_server.load("audio/sfx/step2.ogg")),
             (SfxKey::Step3, asset_server.load("audio/sfx/step3.ogg")),
             (SfxKey::Step4, asset_server.load("audio/sfx/step4.ogg")),
+            // Until the game ships a sound of its own; mods may replace it.
+            (
+                SfxKey::ChipSale,
+                asset_server.load("audio/sfx/button_press.ogg"),
+            ),
         ]
         .into()
     }
@@ -0,0 +1,306 @@
+//! Camera of the seated players: it glides between views instead of jumping,
+//! and can be orbited, zoomed and panned within the limits of the table.
+//!
+//! Right drag orbits, the mouse wheel zooms, WASD or the arrows pan, and
+//! 1, 2 and 3 switch to the own seat, the whole table and the racing track.
+//! Spectators steer their own free camera instead.
+
+use std::{f32::consts::PI, time::Duration};
+
+use bevy::{
+    input::mouse::{MouseMotion, MouseWheel},
+    prelude::*,
+};
+use bevy_tweening::{
+    lens::{TransformPositionLens, TransformRotationLens},
+    Animator, EaseFunction, Tracks, Tween, TweenCompleted,
+};
+
+use super::{
+    cards::{GameState, SwitchPlayer},
+    settings::MatchSettings,
+    spawn::level::{Seat, SpawnBoard},
+    spectator::Spectator,
+};
+use crate::{net::client::NetClient, screen::Screen, GameCamera};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(place_camera);
+    app.add_systems(
+        Update,
+        (
+            follow_current_player,
+            switch_view,
+            control_camera,
+            finish_transition,
+            apply_orbit,
+        )
+            .chain()
+            .run_if(in_state(Screen::Playing).and_then(not(resource_exists::<Spectator>))),
+    );
+}
+
+const TRANSITION_DURATION: Duration = Duration::from_millis(700);
+/// Marks the tween moving the camera, to know when it is over.
+const TRANSITION_TWEEN: u64 = 2;
+/// Radians turned per pixel of mouse travel.
+const ORBIT_SPEED: f32 = 0.005;
+/// Units per second the focus pans at.
+const PAN_SPEED: f32 = 12.0;
+/// Share of the distance zoomed per step of the mouse wheel.
+const ZOOM_STEP: f32 = 0.1;
+const MIN_PITCH: f32 = 0.3;
+const MAX_PITCH: f32 = 1.5;
+const MIN_DISTANCE: f32 = 6.0;
+const MAX_DISTANCE: f32 = 50.0;
+/// Corners of the area the focus may be panned in.
+const MIN_FOCUS: Vec3 = Vec3::new(-16.0, 0.0, -10.0);
+const MAX_FOCUS: Vec3 = Vec3::new(24.0, 0.0, 10.0);
+const RACING_TRACK: Vec3 = Vec3::new(-7.6, 0.0, 0.0);
+
+/// Camera circling a point of the table.
+#[derive(Clone, Copy, Debug)]
+struct Orbit {
+    focus: Vec3,
+    /// Angle around the vertical; `0.0` looks from the near side.
+    yaw: f32,
+    /// Angle above the table.
+    pitch: f32,
+    distance: f32,
+}
+
+impl Orbit {
+    /// The orbit putting the camera at `transform`, looking at `focus`.
+    fn from_transform(transform: &Transform, focus: Vec3) -> Self {
+        let offset = transform.translation - focus;
+        let distance = offset.length();
+        Self {
+            focus,
+            yaw: offset.x.atan2(offset.z),
+            pitch: (offset.y / distance).asin(),
+            distance,
+        }
+    }
+
+    fn seat(player: usize) -> Self {
+        let seat = Seat(player);
+        Self::from_transform(&seat.camera_transform(), seat.camera_focus())
+    }
+
+    /// Every seat from above, the racing track included.
+    fn table(num_players: usize) -> Self {
+        let last_seat = Seat(num_players.max(1)).offset();
+        Self {
+            focus: Vec3::new((RACING_TRACK.x + last_seat.x) / 2.0, 0.0, 0.0),
+            yaw: 0.0,
+            pitch: 1.2,
+            distance: 30.0 + last_seat.x,
+        }
+    }
+
+    fn racing_track(yaw: f32) -> Self {
+        Self {
+            focus: RACING_TRACK,
+            yaw,
+            pitch: 1.2,
+            distance: 14.0,
+        }
+    }
+
+    fn clamped(mut self) -> Self {
+        self.focus = self.focus.clamp(MIN_FOCUS, MAX_FOCUS);
+        self.pitch = self.pitch.clamp(MIN_PITCH, MAX_PITCH);
+        self.distance = self.distance.clamp(MIN_DISTANCE, MAX_DISTANCE);
+        self
+    }
+
+    fn transform(&self) -> Transform {
+        let direction = Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        );
+        Transform::from_translation(self.focus + direction * self.distance)
+            .looking_at(self.focus, Vec3::Y)
+    }
+}
+
+#[derive(Component)]
+struct CameraRig {
+    orbit: Orbit,
+    /// Whether a tween is moving the camera, which input must then leave be.
+    in_transition: bool,
+}
+
+/// The seat the local player watches the match from.
+fn own_seat(state: &GameState, net: Option<&NetClient>) -> usize {
+    net.and_then(|net| net.player).unwrap_or(state.player)
+}
+
+fn place_camera(
+    _trigger: Trigger<SpawnBoard>,
+    mut commands: Commands,
+    state: Res<GameState>,
+    net: Option<Res<NetClient>>,
+    cameras: Query<Entity, With<GameCamera>>,
+) {
+    for camera in &cameras {
+        commands
+            .entity(camera)
+            .remove::<Animator<Transform>>()
+            .insert(CameraRig {
+                orbit: Orbit::seat(own_seat(&state, net.as_deref())),
+                in_transition: false,
+            });
+    }
+}
+
+/// Glides the camera over to `orbit`.
+fn start_transition(
+    commands: &mut Commands,
+    camera: Entity,
+    rig: &mut CameraRig,
+    transform: &Transform,
+    orbit: Orbit,
+) {
+    let end = orbit.transform();
+    let position: Tween<Transform> = Tween::new(
+        EaseFunction::QuadraticInOut,
+        TRANSITION_DURATION,
+        TransformPositionLens {
+            start: transform.translation,
+            end: end.translation,
+        },
+    )
+    .with_completed_event(TRANSITION_TWEEN);
+    let rotation: Tween<Transform> = Tween::new(
+        EaseFunction::QuadraticInOut,
+        TRANSITION_DURATION,
+        TransformRotationLens {
+            start: transform.rotation,
+            end: end.rotation,
+        },
+    );
+    commands
+        .entity(camera)
+        .insert(Animator::new(Tracks::new([position, rotation])));
+    rig.orbit = orbit;
+    rig.in_transition = true;
+}
+
+fn follow_current_player(
+    mut commands: Commands,
+    mut er_switch_player: EventReader<SwitchPlayer>,
+    mut cameras: Query<(Entity, &mut CameraRig, &Transform)>,
+) {
+    let Some(event) = er_switch_player.read().last() else {
+        return;
+    };
+    for (camera, mut rig, transform) in &mut cameras {
+        start_transition(
+            &mut commands,
+            camera,
+            &mut rig,
+            transform,
+            Orbit::seat(event.player),
+        );
+    }
+}
+
+fn switch_view(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    net: Option<Res<NetClient>>,
+    mut cameras: Query<(Entity, &mut CameraRig, &Transform)>,
+) {
+    let seat = own_seat(&state, net.as_deref());
+    let orbit = if keys.just_pressed(KeyCode::Digit1) {
+        Orbit::seat(seat)
+    } else if keys.just_pressed(KeyCode::Digit2) {
+        Orbit::table(match_settings.num_players())
+    } else if keys.just_pressed(KeyCode::Digit3) {
+        // Seen from the side of the table the player sits on.
+        let yaw = if Seat(seat).side() > 0.0 { 0.0 } else { PI };
+        Orbit::racing_track(yaw)
+    } else {
+        return;
+    };
+    for (camera, mut rig, transform) in &mut cameras {
+        start_transition(&mut commands, camera, &mut rig, transform, orbit);
+    }
+}
+
+fn control_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut er_mouse_motion: EventReader<MouseMotion>,
+    mut er_mouse_wheel: EventReader<MouseWheel>,
+    // The wheel scrolls panels rather than zooming when over them.
+    ui_query: Query<&Interaction, With<Node>>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    let motion = er_mouse_motion
+        .read()
+        .map(|event| event.delta)
+        .sum::<Vec2>();
+    let zoom_input = er_mouse_wheel.read().map(|event| event.y).sum::<f32>();
+    let over_ui = ui_query
+        .iter()
+        .any(|interaction| *interaction != Interaction::None);
+
+    let pressed = |codes: &[KeyCode]| keys.any_pressed(codes.iter().copied()) as i32 as f32;
+    let forward_input =
+        pressed(&[KeyCode::KeyW, KeyCode::ArrowUp]) - pressed(&[KeyCode::KeyS, KeyCode::ArrowDown]);
+    let right_input = pressed(&[KeyCode::KeyD, KeyCode::ArrowRight])
+        - pressed(&[KeyCode::KeyA, KeyCode::ArrowLeft]);
+
+    for mut rig in &mut cameras {
+        if rig.in_transition {
+            continue;
+        }
+        let mut orbit = rig.orbit;
+        if mouse.pressed(MouseButton::Right) {
+            orbit.yaw -= motion.x * ORBIT_SPEED;
+            orbit.pitch += motion.y * ORBIT_SPEED;
+        }
+        if !over_ui {
+            orbit.distance *= 1.0 - zoom_input * ZOOM_STEP;
+        }
+        // Panning follows the way the camera faces.
+        let forward = -Vec3::new(orbit.yaw.sin(), 0.0, orbit.yaw.cos());
+        let right = Vec3::new(orbit.yaw.cos(), 0.0, -orbit.yaw.sin());
+        orbit.focus +=
+            (forward * forward_input + right * right_input) * PAN_SPEED * time.delta_seconds();
+        rig.orbit = orbit.clamped();
+    }
+}
+
+fn finish_transition(
+    mut commands: Commands,
+    mut er_tween_completed: EventReader<TweenCompleted>,
+    mut cameras: Query<&mut CameraRig>,
+) {
+    for event in er_tween_completed.read() {
+        if event.user_data != TRANSITION_TWEEN {
+            continue;
+        }
+        let Ok(mut rig) = cameras.get_mut(event.entity) else {
+            continue;
+        };
+        rig.in_transition = false;
+        commands
+            .entity(event.entity)
+            .remove::<Animator<Transform>>();
+    }
+}
+
+fn apply_orbit(mut cameras: Query<(&CameraRig, &mut Transform)>) {
+    for (rig, mut transform) in &mut cameras {
+        if !rig.in_transition {
+            *transform = rig.orbit.transform();
+        }
+    }
+}
@@ -0,0 +1,413 @@
+//! Campaign of scripted scenarios, each unlocking the next one.
+//!
+//! Scenarios are data files under `assets/scenarios`, listed in play order by
+//! `campaign.ron`. They set up a regular match through [`GameState`] and the
+//! card loaders and only add their own win and loss conditions.
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cards::{AdvancePhase, CardType, ChipType, DropChip, GameOver, GameState},
+    locale::Locale,
+    log::GameLog,
+    settings::{Controller, MatchSettings, PlayerSettings, MAX_PLAYERS, MIN_PLAYERS},
+    spawn::{level::SpawnBoard, ui::CardGameUIAction},
+};
+use crate::{screen::Screen, ui::prelude::InteractionQuery};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<Scenario>()
+        .init_asset::<Campaign>()
+        .register_asset_loader(ScenarioLoader)
+        .register_asset_loader(CampaignLoader)
+        .init_resource::<CampaignHandle>()
+        .insert_resource(CampaignProgress::load())
+        .observe(place_starting_chips)
+        .add_systems(
+            Update,
+            (check_scenario_outcome, handle_back_to_campaign)
+                .run_if(resource_exists::<ActiveScenario>),
+        );
+}
+
+/// Where the campaign progress is kept between sessions.
+#[cfg(not(target_family = "wasm"))]
+const PROGRESS_PATH: &str = "campaign-progress.ron";
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Objective {
+    /// Reach this net worth before the given turn ends.
+    ReachBank { amount: i32, by_turn: usize },
+    /// Drive the rival seat out of business before the given turn ends.
+    BankruptRival { rival: usize, by_turn: usize },
+}
+
+impl Objective {
+    pub fn by_turn(&self) -> usize {
+        match self {
+            Objective::ReachBank { by_turn, .. } | Objective::BankruptRival { by_turn, .. } => {
+                *by_turn
+            }
+        }
+    }
+
+    pub fn describe(&self, locale: &Locale) -> String {
+        match self {
+            Objective::ReachBank { amount, by_turn } => locale.format(
+                "objective-reach-bank",
+                &[
+                    ("amount", locale.money(*amount)),
+                    ("turn", by_turn.to_string()),
+                ],
+            ),
+            Objective::BankruptRival { rival, by_turn } => locale.format(
+                "objective-bankrupt-rival",
+                &[("rival", rival.to_string()), ("turn", by_turn.to_string())],
+            ),
+        }
+    }
+}
+
+/// Extra ways to lose a scenario besides running out of turns.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Failure {
+    Bankrupt,
+    HeatReaches(i32),
+}
+
+/// Chips put in a player's production area before the first turn.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct StartingChips {
+    pub player: usize,
+    pub chip_type: ChipType,
+    pub count: usize,
+}
+
+#[derive(Asset, TypePath, Clone, Debug, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    pub briefing: String,
+    /// The human plays the first seat, the AI every other one.
+    pub num_players: usize,
+    pub bank: Vec<i32>,
+    #[serde(default)]
+    pub chips: Vec<StartingChips>,
+    /// Replaces the standard playing deck when set.
+    #[serde(default)]
+    pub playing_deck: Option<Vec<(CardType, usize)>>,
+    /// Replaces the standard event deck when set.
+    #[serde(default)]
+    pub event_deck: Option<Vec<(CardType, usize)>>,
+    pub objective: Objective,
+    #[serde(default)]
+    pub failures: Vec<Failure>,
+}
+
+impl Scenario {
+    /// Catches what would otherwise index past the seats of the match.
+    fn check(&self) -> Result<(), String> {
+        let what = format!("Scenario \"{}\"", self.name);
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&self.num_players) {
+            return Err(format!(
+                "{}: the number of players must be between {} and {}",
+                what, MIN_PLAYERS, MAX_PLAYERS
+            ));
+        }
+        let seats = 1..=self.num_players;
+        if let Some(chips) = self
+            .chips
+            .iter()
+            .find(|chips| !seats.contains(&chips.player))
+        {
+            return Err(format!(
+                "{}: starting chips for player {}, who isn't seated",
+                what, chips.player
+            ));
+        }
+        if let Objective::BankruptRival { rival, .. } = self.objective {
+            // The human plays the first seat.
+            if rival == 1 || !seats.contains(&rival) {
+                return Err(format!("{}: player {} can't be the rival", what, rival));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn match_settings(&self, seed: u64) -> MatchSettings {
+        let players = (1..=self.num_players)
+            .map(|player| PlayerSettings {
+                controller: if player == 1 {
+                    Controller::Human
+                } else {
+                    Controller::Ai
+                },
+                ..PlayerSettings::new(player)
+            })
+            .collect();
+
+        MatchSettings {
+            players,
+            seed,
+            ..default()
+        }
+    }
+
+    pub fn game_state(&self, settings: &MatchSettings) -> GameState {
+        let mut state = GameState::from_settings(settings);
+        for (player, bank) in self.bank.iter().enumerate().take(self.num_players) {
+            state.bank[player] = *bank;
+        }
+        state.max_turns = self.objective.by_turn();
+        // The racing track measures the way to the money objective.
+        if let Objective::ReachBank { amount, .. } = self.objective {
+            state.winning_bank = amount;
+        }
+        state
+    }
+
+    /// `Some(true)` once the human won, `Some(false)` once they lost.
+    pub fn outcome(&self, state: &GameState) -> Option<bool> {
+        let failed = self.failures.iter().any(|failure| match failure {
+            Failure::Bankrupt => state.is_bankrupt(1),
+            Failure::HeatReaches(heat) => state.get_heat(1) >= *heat,
+        });
+        if failed {
+            return Some(false);
+        }
+
+        let won = match self.objective {
+            Objective::ReachBank { amount, .. } => state.net_worth(1) >= amount,
+            Objective::BankruptRival { rival, .. } => state.is_bankrupt(rival),
+        };
+        if won {
+            return Some(true);
+        }
+
+        if state.turn_number > self.objective.by_turn() {
+            return Some(false);
+        }
+
+        None
+    }
+}
+
+/// Ordered list of scenarios making up the campaign.
+#[derive(Asset, TypePath, Debug)]
+pub struct Campaign {
+    #[dependency]
+    pub scenarios: Vec<Handle<Scenario>>,
+}
+
+/// On-disk shape of `campaign.ron`: scenario paths relative to `assets`.
+#[derive(Deserialize)]
+struct CampaignFile {
+    scenarios: Vec<String>,
+}
+
+#[derive(Default)]
+struct ScenarioLoader;
+
+impl AssetLoader for ScenarioLoader {
+    type Asset = Scenario;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Scenario, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let scenario: Scenario = ron::de::from_bytes(&bytes)?;
+        scenario.check()?;
+        Ok(scenario)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scenario.ron"]
+    }
+}
+
+#[derive(Default)]
+struct CampaignLoader;
+
+impl AssetLoader for CampaignLoader {
+    type Asset = Campaign;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        load_context: &'a mut LoadContext<'_>,
+    ) -> Result<Campaign, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let file: CampaignFile = ron::de::from_bytes(&bytes)?;
+        Ok(Campaign {
+            scenarios: file
+                .scenarios
+                .into_iter()
+                .map(|path| load_context.load(path))
+                .collect(),
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["campaign.ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct CampaignHandle {
+    pub campaign: Handle<Campaign>,
+    pub tutorial: Handle<Scenario>,
+}
+
+impl FromWorld for CampaignHandle {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            campaign: asset_server.load("scenarios/main.campaign.ron"),
+            tutorial: asset_server.load("scenarios/tutorial.scenario.ron"),
+        }
+    }
+}
+
+/// Number of scenarios beaten, saved between sessions.
+#[derive(Resource, Default, Debug, Serialize, Deserialize)]
+pub struct CampaignProgress {
+    pub completed: usize,
+}
+
+impl CampaignProgress {
+    /// The first scenario is always open, every win opens the next one.
+    pub fn is_unlocked(&self, index: usize) -> bool {
+        index <= self.completed
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        std::fs::read_to_string(PROGRESS_PATH)
+            .ok()
+            .and_then(|progress| ron::from_str(&progress).ok())
+            .unwrap_or_default()
+    }
+
+    /// Progress isn't persisted on the web build.
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn save(&self) {
+        let result = ron::to_string(self)
+            .map_err(|error| error.to_string())
+            .and_then(|progress| {
+                std::fs::write(PROGRESS_PATH, progress).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("Could not save campaign progress: {}", error);
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn save(&self) {}
+}
+
+/// Scenario being played; absent during regular matches.
+#[derive(Resource)]
+pub struct ActiveScenario {
+    /// Position in the campaign; `None` for scenarios outside of it, like
+    /// the tutorial.
+    pub index: Option<usize>,
+    pub scenario: Scenario,
+    pub finished: bool,
+}
+
+fn place_starting_chips(
+    _trigger: Trigger<SpawnBoard>,
+    scenario: Option<Res<ActiveScenario>>,
+    mut ew_drop_chip: EventWriter<DropChip>,
+) {
+    let Some(active) = scenario else {
+        return;
+    };
+
+    for chips in active.scenario.chips.iter() {
+        for _ in 0..chips.count {
+            ew_drop_chip.send(DropChip {
+                chip_type: chips.chip_type,
+                area: 1,
+                player: chips.player,
+            });
+        }
+    }
+}
+
+fn check_scenario_outcome(
+    mut er_advance_phase: EventReader<AdvancePhase>,
+    mut active: ResMut<ActiveScenario>,
+    mut progress: ResMut<CampaignProgress>,
+    game_state: Res<GameState>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+    mut ew_game_over: EventWriter<GameOver>,
+) {
+    for _ in er_advance_phase.read() {
+        if active.finished {
+            continue;
+        }
+        let Some(won) = active.scenario.outcome(&game_state) else {
+            continue;
+        };
+        active.finished = true;
+
+        let key = if won {
+            "log-scenario-complete"
+        } else {
+            "log-scenario-failed"
+        };
+        let text = locale.format(key, &[("name", active.scenario.name.clone())]);
+        log.push(game_state.turn_number, text);
+        if won {
+            if let Some(index) = active.index {
+                if progress.completed <= index {
+                    progress.completed = index + 1;
+                    progress.save();
+                }
+            }
+            ew_game_over.send(GameOver { player_won: 1 });
+        } else {
+            let rival = (2..=game_state.num_players())
+                .max_by_key(|player| game_state.net_worth(*player))
+                .unwrap_or(1);
+            ew_game_over.send(GameOver { player_won: rival });
+        }
+    }
+}
+
+/// Leaves a finished scenario for the campaign screen, where the next one is
+/// now unlocked.
+fn handle_back_to_campaign(
+    mut commands: Commands,
+    mut button_query: InteractionQuery<&CardGameUIAction>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed)
+            && *action == CardGameUIAction::ButtonBackToCampaign
+        {
+            commands.remove_resource::<ActiveScenario>();
+            next_screen.set(Screen::Campaign);
+        }
+    }
+}
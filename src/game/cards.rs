@@ -1,18 +1,31 @@
-use bevy::{app::App, prelude::*};
+use bevy::{
+    app::App,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use bevy_la_mesa::events::{
     AlignCardsInHand, AlignChipsOnTable, PlaceCardOffTable, PlaceCardOnTable,
 };
-use bevy_la_mesa::{Card, CardMetadata, CardOnTable, Chip, ChipArea, Deck};
+use bevy_la_mesa::{Card, CardMetadata, CardOnTable, Chip, ChipArea, Deck, Hand};
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
 use std::fmt::Debug;
 use std::marker::Send;
 
-use crate::GameCamera;
+use crate::game::{
+    campaign::ActiveScenario,
+    locale::Locale,
+    log::GameLog,
+    script::{self, ScriptRun},
+    settings::{GameRng, MatchSettings},
+};
+use crate::net::client::NetClient;
 
 #[derive(Resource)]
 pub struct PhaseTimer(pub Timer);
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CardType {
     #[default]
     Attack,
@@ -21,9 +34,225 @@ pub enum CardType {
     Cannabis,
     Drought,
     Export,
+    PoliceRaid,
     LocalMarket,
     Train,
     Truck,
+    Warehouse,
+    MarketCrash,
+    BorderClosure,
+    PoliceCrackdown,
+    BumperHarvest,
+    Informant,
+    /// No built-in effect; everything it does comes from its script.
+    Scripted,
+}
+
+/// Kind of card, named after the phase that resolves it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CardCategory {
+    Production,
+    Transportation,
+    Sales,
+    Action,
+    Event,
+    Special,
+}
+
+impl CardType {
+    pub fn category(&self) -> CardCategory {
+        match self {
+            CardType::Cocaine | CardType::Cannabis => CardCategory::Production,
+            CardType::Truck | CardType::Train => CardCategory::Transportation,
+            CardType::Export | CardType::LocalMarket => CardCategory::Sales,
+            CardType::Attack | CardType::Warehouse => CardCategory::Action,
+            CardType::BigDeal
+            | CardType::Drought
+            | CardType::PoliceRaid
+            | CardType::MarketCrash
+            | CardType::BorderClosure
+            | CardType::PoliceCrackdown
+            | CardType::BumperHarvest
+            | CardType::Informant => CardCategory::Event,
+            CardType::Scripted => CardCategory::Special,
+        }
+    }
+
+    /// Events that hit every player at the table instead of only the one who
+    /// drew them.
+    pub fn is_global_event(&self) -> bool {
+        matches!(
+            self,
+            CardType::MarketCrash | CardType::BorderClosure | CardType::PoliceCrackdown
+        )
+    }
+
+    /// Transport characteristics of vehicle cards.
+    pub fn vehicle(&self) -> Option<Vehicle> {
+        match self {
+            CardType::Truck => Some(Vehicle {
+                capacity: 10,
+                cocaine_cost: 60,
+                cannabis_cost: 20,
+                travel_turns: 0,
+                interception_risk: 10,
+            }),
+            CardType::Train => Some(Vehicle {
+                capacity: 25,
+                cocaine_cost: 30,
+                cannabis_cost: 10,
+                travel_turns: 1,
+                interception_risk: 3,
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vehicle {
+    /// Chips carried per trip.
+    pub capacity: usize,
+    /// Shipping cost per chip.
+    pub cocaine_cost: i32,
+    pub cannabis_cost: i32,
+    /// Turns spent on the road; 0 means chips arrive right away.
+    pub travel_turns: usize,
+    /// Chance for each chip to be intercepted, in percent.
+    pub interception_risk: i32,
+}
+
+impl Vehicle {
+    pub fn cost(&self, chip_type: ChipType) -> i32 {
+        match chip_type {
+            ChipType::Cocaine => self.cocaine_cost,
+            ChipType::Cannabis => self.cannabis_cost,
+        }
+    }
+}
+
+/// Buyers reached through sales cards. Each market only takes a limited
+/// number of chips per turn, shared by all players.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Market {
+    Export,
+    Local,
+}
+
+impl CardType {
+    pub fn market(&self) -> Option<Market> {
+        match self {
+            CardType::Export => Some(Market::Export),
+            CardType::LocalMarket => Some(Market::Local),
+            _ => None,
+        }
+    }
+}
+
+impl Market {
+    /// Chips of a commodity the market buys per turn.
+    pub fn demand(&self, chip_type: ChipType) -> usize {
+        match (self, chip_type) {
+            (Market::Export, ChipType::Cocaine) => 15,
+            (Market::Export, ChipType::Cannabis) => 10,
+            (Market::Local, ChipType::Cocaine) => 4,
+            (Market::Local, ChipType::Cannabis) => 8,
+        }
+    }
+
+    pub fn price(&self, chip_type: ChipType) -> i32 {
+        match (self, chip_type) {
+            (Market::Export, ChipType::Cocaine) => 1000,
+            (Market::Export, ChipType::Cannabis) => 450,
+            (Market::Local, _) => chip_type.price(),
+        }
+    }
+
+    /// Turns before the money of a sale reaches the bank.
+    pub fn payment_delay(&self) -> usize {
+        match self {
+            Market::Export => 1,
+            Market::Local => 0,
+        }
+    }
+
+    /// Chance for each chip to be seized at the border, in percent.
+    pub fn seizure_risk(&self) -> i32 {
+        match self {
+            Market::Export => 5,
+            Market::Local => 0,
+        }
+    }
+
+    pub fn heat_per_chip(&self) -> i32 {
+        match self {
+            Market::Export => 1,
+            Market::Local => 2,
+        }
+    }
+}
+
+/// Money from a sale that hasn't been paid out yet.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingPayment {
+    pub player: usize,
+    pub amount: i32,
+    pub due_turn: usize,
+}
+
+/// Which commodities a player loads on their vehicles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cargo {
+    #[default]
+    Mixed,
+    CocaineFirst,
+    CannabisFirst,
+    CocaineOnly,
+    CannabisOnly,
+}
+
+impl Cargo {
+    pub fn next(self) -> Self {
+        match self {
+            Cargo::Mixed => Cargo::CocaineFirst,
+            Cargo::CocaineFirst => Cargo::CannabisFirst,
+            Cargo::CannabisFirst => Cargo::CocaineOnly,
+            Cargo::CocaineOnly => Cargo::CannabisOnly,
+            Cargo::CannabisOnly => Cargo::Mixed,
+        }
+    }
+
+    /// Loading order of the chips available for a trip.
+    pub fn order<T>(self, cocaine: Vec<T>, cannabis: Vec<T>) -> Vec<T> {
+        match self {
+            Cargo::Mixed => {
+                let mut mixed = vec![];
+                let mut cocaine = cocaine.into_iter();
+                let mut cannabis = cannabis.into_iter();
+                loop {
+                    match (cannabis.next(), cocaine.next()) {
+                        (None, None) => break,
+                        (first, second) => mixed.extend(first.into_iter().chain(second)),
+                    }
+                }
+                mixed
+            }
+            Cargo::CocaineFirst => cocaine.into_iter().chain(cannabis).collect(),
+            Cargo::CannabisFirst => cannabis.into_iter().chain(cocaine).collect(),
+            Cargo::CocaineOnly => cocaine,
+            Cargo::CannabisOnly => cannabis,
+        }
+    }
+}
+
+/// Chip area marker of chips that are on the road to the sales area.
+pub const TRANSIT_AREA: usize = 3;
+
+/// Chips loaded on a vehicle that hasn't arrived yet.
+#[derive(Component, Debug)]
+pub struct InTransit {
+    pub arrival_turn: usize,
+    pub interception_risk: i32,
 }
 
 #[derive(Component)]
@@ -31,18 +260,31 @@ pub struct ActiveEventCard {
     pub player: usize,
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ChipType {
     #[default]
     Cocaine,
     Cannabis,
 }
 
-#[derive(Default, Clone, Debug)]
+impl ChipType {
+    /// What a chip is worth when it is sold or seized.
+    pub fn price(&self) -> i32 {
+        match self {
+            ChipType::Cocaine => 800,
+            ChipType::Cannabis => 400,
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Kard {
     pub card_type: CardType,
     pub price: i32,
     pub filename: String,
+    /// Rhai source run on top of the built-in effect, see [`super::script`].
+    #[serde(default)]
+    pub script: Option<String>,
 }
 
 impl CardMetadata for Kard {
@@ -58,42 +300,56 @@ pub fn load_playing_deck(num_players: usize) -> Vec<Kard> {
         card_type: CardType::Attack,
         price: 300,
         filename: "tarjetas/attack.png".to_string(),
+        script: None,
     };
 
     let cocaine = Kard {
         card_type: CardType::Cocaine,
         price: 1000,
         filename: "tarjetas/cocaine.png".to_string(),
+        script: None,
     };
 
     let export = Kard {
         card_type: CardType::Export,
         price: 0,
         filename: "tarjetas/export.png".to_string(),
+        script: None,
     };
 
     let local_market = Kard {
         card_type: CardType::LocalMarket,
         price: 0,
         filename: "tarjetas/local-market.png".to_string(),
+        script: None,
     };
 
     let marijuana = Kard {
         card_type: CardType::Cannabis,
         price: 500,
         filename: "tarjetas/marijuana.png".to_string(),
+        script: None,
     };
 
     let train = Kard {
         card_type: CardType::Train,
         price: 600,
         filename: "tarjetas/train.png".to_string(),
+        script: None,
     };
 
     let truck = Kard {
         card_type: CardType::Truck,
         price: 300,
         filename: "tarjetas/truck.png".to_string(),
+        script: None,
+    };
+
+    let warehouse = Kard {
+        card_type: CardType::Warehouse,
+        price: 800,
+        filename: "tarjetas/resources-production.png".to_string(),
+        script: None,
     };
 
     let mut deck: Vec<Kard> = vec![];
@@ -115,6 +371,8 @@ pub fn load_playing_deck(num_players: usize) -> Vec<Kard> {
         deck.push(export.clone());
 
         deck.push(attack.clone());
+
+        deck.push(warehouse.clone());
     }
 
     deck
@@ -125,24 +383,98 @@ pub fn load_event_deck(num_players: usize) -> Vec<Kard> {
         card_type: CardType::BigDeal,
         price: 0,
         filename: "tarjetas/big-deal.png".to_string(),
+        script: None,
     };
 
     let drought = Kard {
         card_type: CardType::Drought,
         price: 0,
         filename: "tarjetas/drought.png".to_string(),
+        script: None,
+    };
+
+    let police_raid = Kard {
+        card_type: CardType::PoliceRaid,
+        price: 0,
+        filename: "tarjetas/archive-1.png".to_string(),
+        script: None,
+    };
+
+    let market_crash = Kard {
+        card_type: CardType::MarketCrash,
+        price: 0,
+        filename: "tarjetas/resources-sales.png".to_string(),
+        script: None,
+    };
+
+    let border_closure = Kard {
+        card_type: CardType::BorderClosure,
+        price: 0,
+        filename: "tarjetas/export.png".to_string(),
+        script: None,
+    };
+
+    let police_crackdown = Kard {
+        card_type: CardType::PoliceCrackdown,
+        price: 0,
+        filename: "tarjetas/archive-1.png".to_string(),
+        script: None,
+    };
+
+    let bumper_harvest = Kard {
+        card_type: CardType::BumperHarvest,
+        price: 0,
+        filename: "tarjetas/marijuana.png".to_string(),
+        script: None,
+    };
+
+    let informant = Kard {
+        card_type: CardType::Informant,
+        price: 0,
+        filename: "tarjetas/debug.png".to_string(),
+        script: None,
     };
 
     let mut deck: Vec<Kard> = vec![];
     for _ in 0..num_players {
         deck.push(drought.clone());
         deck.push(big_deal.clone());
+        deck.push(police_raid.clone());
+        deck.push(bumper_harvest.clone());
+        deck.push(informant.clone());
     }
 
+    // Global events hit everybody, so a single copy of each is enough.
+    deck.push(market_crash);
+    deck.push(border_closure);
+    deck.push(police_crackdown);
+
     deck
 }
 
-#[derive(Default, Debug, PartialEq)]
+/// Builds a deck from `(card type, copies)` pairs, reusing the cards of the
+/// standard decks.
+pub fn load_custom_deck(cards: &[(CardType, usize)]) -> Vec<Kard> {
+    let templates = load_playing_deck(1)
+        .into_iter()
+        .chain(load_event_deck(1))
+        .collect::<Vec<_>>();
+
+    let mut deck: Vec<Kard> = vec![];
+    for &(card_type, copies) in cards {
+        let Some(template) = templates.iter().find(|kard| kard.card_type == card_type) else {
+            warn!("No card template for {:?}", card_type);
+            continue;
+        };
+        for _ in 0..copies {
+            deck.push(template.clone());
+        }
+    }
+
+    deck
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TurnPhase {
     #[default]
     Prepare,
@@ -156,20 +488,65 @@ pub enum TurnPhase {
     End,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum EffectType {
     Drought,
     Attack,
+    MarketCrash,
+    BorderClosure,
+    BumperHarvest,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Effect {
     pub effect_type: EffectType,
     pub player: usize,
-    pub turn_number: usize,
+    /// Seat turn the effect began on, see [`GameState::seat_turn`].
+    pub seat_turn: usize,
+    /// Rounds the effect lasts, each one ending on the seat it began on.
     pub duration: usize,
 }
 
+impl Effect {
+    /// Turns the effect still lasts, counting the current one.
+    pub fn turns_left(&self, state: &GameState) -> usize {
+        (self.seat_turn + self.duration * state.num_players)
+            .saturating_sub(state.seat_turn())
+            .div_ceil(state.num_players)
+    }
+}
+
+/// Heat never goes above this value; at this level a police raid is certain.
+pub const MAX_HEAT: i32 = 100;
+/// Heat lost at the end of each of the player's turns.
+pub const HEAT_DECAY: i32 = 5;
+/// Heat gained by playing an Attack card.
+pub const HEAT_PER_ATTACK: i32 = 10;
+
+/// Chips each production or sales area holds before a Warehouse upgrade.
+pub const BASE_STORAGE_CAPACITY: usize = 20;
+/// Extra room per area granted by a Warehouse card.
+pub const STORAGE_UPGRADE: usize = 10;
+
+/// Play areas in front of each player, one card each.
+pub const TABLE_SLOTS: usize = 5;
+
+/// Personal playing decks use this marker plus the player's seat, keeping
+/// clear of the shared decks 1 and 2.
+pub const PERSONAL_DECK_MARKER: usize = 10;
+
+/// Amount lent by the loan shark per loan.
+pub const LOAN_AMOUNT: i32 = 1000;
+/// Credit limit of a single player.
+pub const MAX_DEBT: i32 = 10000;
+/// Interest charged on outstanding debt every End phase, in percent.
+pub const LOAN_INTEREST_PERCENT: i32 = 10;
+
+/// Fine per point of heat charged by a police crackdown.
+pub const CRACKDOWN_FINE_PER_HEAT: i32 = 20;
+/// Extra chips per production card during a bumper harvest.
+pub const BUMPER_HARVEST_BONUS: i32 = 3;
+
 #[derive(Resource)]
 pub struct GameState {
     pub turn_number: usize,
@@ -177,6 +554,23 @@ pub struct GameState {
     pub phase: TurnPhase,
     pub player: usize,
     pub bank: Vec<i32>,
+    /// Money owed to the loan shark, kept apart from `bank`.
+    pub debt: Vec<i32>,
+    /// Police attention, from 0 to [`MAX_HEAT`].
+    pub heat: Vec<i32>,
+    /// What each player loads on their vehicles.
+    pub cargo: Vec<Cargo>,
+    /// Chips bought by each market during the current turn.
+    pub market_sales: HashMap<(Market, ChipType), usize>,
+    pub pending_payments: Vec<PendingPayment>,
+    /// Chips each player can keep in their production and sales areas.
+    pub storage_capacity: Vec<[usize; 2]>,
+    pub spoilage_turns: Option<usize>,
+    pub winning_bank: i32,
+    pub max_turns: usize,
+    /// Whether every player draws from a deck of their own instead of the
+    /// shared deck 1.
+    pub personal_decks: bool,
     num_players: usize,
 }
 
@@ -195,9 +589,11 @@ impl GameState {
                 if self.player == self.num_players {
                     self.turn_number += 1;
                     self.player = 1;
+                    self.market_sales.clear();
                 } else {
                     self.player += 1;
                 }
+                self.collect_payments(self.player);
                 TurnPhase::Prepare
             }
         };
@@ -210,30 +606,199 @@ impl GameState {
             phase: TurnPhase::Prepare,
             player: 1,
             bank: vec![5000; num_players],
+            debt: vec![0; num_players],
+            heat: vec![0; num_players],
+            cargo: vec![Cargo::default(); num_players],
+            market_sales: HashMap::new(),
+            pending_payments: vec![],
+            storage_capacity: vec![[BASE_STORAGE_CAPACITY; 2]; num_players],
+            spoilage_turns: None,
+            winning_bank: 50000,
+            max_turns: 20,
+            personal_decks: false,
             num_players,
             effects: vec![],
         }
     }
 
+    pub fn from_settings(settings: &MatchSettings) -> Self {
+        let num_players = settings.num_players();
+        Self {
+            bank: vec![settings.preset.starting_bank(); num_players],
+            winning_bank: settings.preset.winning_bank(),
+            max_turns: settings.preset.max_turns(),
+            spoilage_turns: settings.spoilage_turns,
+            personal_decks: settings.players.iter().any(|player| player.deck.is_some()),
+            ..Self::new(num_players)
+        }
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.num_players
+    }
+
+    /// Marker of the deck `player` draws from and discards to.
+    pub fn playing_deck(&self, player: usize) -> usize {
+        if self.personal_decks {
+            PERSONAL_DECK_MARKER + player
+        } else {
+            1
+        }
+    }
+
+    /// The player seated after `player`, wrapping around the table.
+    pub fn next_player(&self, player: usize) -> usize {
+        player % self.num_players + 1
+    }
+
     pub fn reset(&mut self) {
         self.turn_number = 1;
         self.phase = TurnPhase::Prepare;
         self.player = 1;
         self.bank = vec![0; self.num_players];
+        self.debt = vec![0; self.num_players];
+        self.heat = vec![0; self.num_players];
+        self.storage_capacity = vec![[BASE_STORAGE_CAPACITY; 2]; self.num_players];
+        self.market_sales.clear();
+        self.pending_payments.clear();
     }
 
     pub fn change_balance(&mut self, player: usize, amount: i32) {
-        self.bank[player - 1] += amount;
+        self.bank[player - 1] = self.bank[player - 1].saturating_add(amount);
     }
 
     pub fn draw_bank(&mut self, player: usize, amount: i32) {
-        self.bank[player - 1] -= amount;
+        self.bank[player - 1] = self.bank[player - 1].saturating_sub(amount);
     }
 
     pub fn get_balance(&self, player: usize) -> i32 {
         self.bank[player - 1]
     }
 
+    pub fn get_debt(&self, player: usize) -> i32 {
+        self.debt[player - 1]
+    }
+
+    /// Bank minus debt; this is what decides who is ahead.
+    pub fn net_worth(&self, player: usize) -> i32 {
+        self.get_balance(player) - self.get_debt(player)
+    }
+
+    pub fn can_borrow(&self, player: usize) -> bool {
+        self.get_debt(player) + LOAN_AMOUNT <= MAX_DEBT
+    }
+
+    /// A player is only bankrupt once the loan shark stops lending.
+    pub fn is_bankrupt(&self, player: usize) -> bool {
+        self.get_balance(player) <= 0 && !self.can_borrow(player)
+    }
+
+    pub fn borrow(&mut self, player: usize) -> bool {
+        if !self.can_borrow(player) {
+            return false;
+        }
+        self.debt[player - 1] += LOAN_AMOUNT;
+        self.bank[player - 1] += LOAN_AMOUNT;
+        true
+    }
+
+    /// Pays back up to one loan, limited by what the player has in the bank.
+    pub fn repay(&mut self, player: usize) -> i32 {
+        let amount = LOAN_AMOUNT
+            .min(self.get_debt(player))
+            .min(self.get_balance(player).max(0));
+        self.debt[player - 1] -= amount;
+        self.bank[player - 1] -= amount;
+        amount
+    }
+
+    /// Charges interest on the player's debt and returns the part of it that
+    /// could not be paid. Unpaid interest is added to the debt.
+    pub fn charge_interest(&mut self, player: usize) -> i32 {
+        let interest = (self.get_debt(player) * LOAN_INTEREST_PERCENT + 99) / 100;
+        let paid = interest.min(self.get_balance(player).max(0));
+        self.bank[player - 1] -= paid;
+
+        let unpaid = interest - paid;
+        self.debt[player - 1] += unpaid;
+        unpaid
+    }
+
+    /// Settles debt with the value of seized goods.
+    pub fn seize(&mut self, player: usize, value: i32) {
+        self.debt[player - 1] = (self.debt[player - 1] - value).max(0);
+    }
+
+    pub fn get_heat(&self, player: usize) -> i32 {
+        self.heat[player - 1]
+    }
+
+    pub fn add_heat(&mut self, player: usize, amount: i32) {
+        self.heat[player - 1] = (self.heat[player - 1] + amount).clamp(0, MAX_HEAT);
+    }
+
+    pub fn market_demand_left(&self, market: Market, chip_type: ChipType) -> usize {
+        market.demand(chip_type).saturating_sub(
+            self.market_sales
+                .get(&(market, chip_type))
+                .copied()
+                .unwrap_or(0),
+        )
+    }
+
+    pub fn record_sale(&mut self, market: Market, chip_type: ChipType) {
+        *self.market_sales.entry((market, chip_type)).or_default() += 1;
+    }
+
+    pub fn add_pending_payment(&mut self, player: usize, amount: i32, delay: usize) {
+        if amount == 0 {
+            return;
+        }
+        self.pending_payments.push(PendingPayment {
+            player,
+            amount,
+            due_turn: self.turn_number + delay,
+        });
+    }
+
+    /// Pays out the player's sales whose payment is due.
+    pub fn collect_payments(&mut self, player: usize) {
+        let turn_number = self.turn_number;
+        let (due, pending): (Vec<_>, Vec<_>) = self
+            .pending_payments
+            .drain(..)
+            .partition(|payment| payment.player == player && payment.due_turn <= turn_number);
+        self.pending_payments = pending;
+        for payment in due {
+            self.change_balance(player, payment.amount);
+        }
+    }
+
+    pub fn get_cargo(&self, player: usize) -> Cargo {
+        self.cargo[player - 1]
+    }
+
+    pub fn cycle_cargo(&mut self, player: usize) {
+        self.cargo[player - 1] = self.cargo[player - 1].next();
+    }
+
+    /// How many chips fit into the production (1) or sales (2) area.
+    pub fn storage_capacity(&self, player: usize, marker: usize) -> usize {
+        self.storage_capacity[player - 1][marker - 1]
+    }
+
+    /// Whether another chip fits into an area already holding `count` chips.
+    /// Chips in transit aren't stored anywhere, so there is always room.
+    pub fn has_room(&self, player: usize, area: usize, count: usize) -> bool {
+        area == TRANSIT_AREA || count < self.storage_capacity(player, area)
+    }
+
+    pub fn upgrade_storage(&mut self, player: usize, amount: usize) {
+        for capacity in self.storage_capacity[player - 1].iter_mut() {
+            *capacity += amount;
+        }
+    }
+
     pub fn add_effect(&mut self, effect_type: EffectType, duration: usize, player: usize) {
         self.effects
             .retain(|effect| !(effect.effect_type == effect_type && effect.player == player));
@@ -241,7 +806,7 @@ impl GameState {
         self.effects.push(Effect {
             effect_type: effect_type,
             player: player,
-            turn_number: self.turn_number,
+            seat_turn: self.seat_turn(),
             duration,
         });
     }
@@ -254,9 +819,24 @@ impl GameState {
             .collect()
     }
 
+    pub fn has_effect(&self, player: usize, effect_type: EffectType) -> bool {
+        self.effects
+            .iter()
+            .any(|effect| effect.player == player && effect.effect_type == effect_type)
+    }
+
+    /// Number of turns played so far by all seats, counting the current one.
+    pub fn seat_turn(&self) -> usize {
+        (self.turn_number - 1) * self.num_players + self.player
+    }
+
+    /// Effects run out on the seat that started them, so that one drawn by
+    /// the last seat of a round still reaches every other seat.
     pub fn remove_expired_effects(&mut self) {
+        let seat_turn = self.seat_turn();
+        let num_players = self.num_players;
         self.effects
-            .retain(|effect| effect.turn_number + effect.duration > self.turn_number);
+            .retain(|effect| effect.seat_turn + effect.duration * num_players > seat_turn);
     }
 }
 
@@ -284,11 +864,27 @@ pub struct MoveChip {
     pub player: usize,
 }
 
+/// Hands a chip over to another player, keeping it in the same kind of area.
+#[derive(Debug, Event)]
+pub struct TransferChip {
+    pub entity: Entity,
+    pub player: usize,
+}
+
 #[derive(Debug, Event)]
 pub struct DiscardChip {
     pub entity: Entity,
 }
 
+/// What an Informant told `player` about the hand of `target`. Only `player`
+/// gets to see it.
+#[derive(Event, Clone, Debug)]
+pub struct InformantReport {
+    pub player: usize,
+    pub target: usize,
+    pub cards: Vec<CardType>,
+}
+
 #[derive(Event)]
 pub struct GameOver {
     pub player_won: usize,
@@ -301,19 +897,25 @@ pub(super) fn plugin(app: &mut App) {
         .add_event::<AdvancePhase>()
         .add_event::<DropChip>()
         .add_event::<MoveChip>()
+        .add_event::<TransferChip>()
         .add_event::<DiscardChip>()
         .add_event::<SwitchPlayer>()
         .add_event::<GameOver>()
+        .add_event::<InformantReport>()
         .insert_resource(PhaseTimer(Timer::from_seconds(0.3, TimerMode::Once)))
         .add_systems(
             Update,
             (
-                apply_card_effects,
-                handle_next_phase,
+                // Online clients leave the rules to the server.
+                (
+                    apply_card_effects,
+                    handle_next_phase,
+                    check_game_over_conditions,
+                )
+                    .run_if(not(resource_exists::<NetClient>)),
                 handle_drop_chip,
                 handle_move_chip,
                 handle_switch_player,
-                check_game_over_conditions,
             ),
         );
 }
@@ -321,24 +923,68 @@ pub(super) fn plugin(app: &mut App) {
 pub fn apply_card_effects(
     mut commands: Commands,
     mut state: ResMut<GameState>,
-    cards_on_table: Query<(Entity, &Card<Kard>, &CardOnTable)>,
+    cards_on_table: Query<(Entity, &Card<Kard>, &CardOnTable, Option<&ScriptRun>)>,
     cards_in_deck: Query<(Entity, &Transform, &Card<Kard>, &Deck)>,
     chips_on_table: Query<(Entity, &Transform, &Chip<ChipType>, &ChipArea)>,
     event_cards_on_table: Query<(Entity, &Card<Kard>, &ActiveEventCard)>,
+    cards_in_hand: Query<(&Card<Kard>, &Hand)>,
     mut ew_place_card_off_table: EventWriter<PlaceCardOffTable>,
     mut ew_place_card_on_table: EventWriter<PlaceCardOnTable>,
     mut ew_drop_chip: EventWriter<DropChip>,
     mut ew_move_chip: EventWriter<MoveChip>,
     mut ew_discard_chip: EventWriter<DiscardChip>,
     mut ew_advance_phase: EventWriter<AdvancePhase>,
+    mut ew_informant_report: EventWriter<InformantReport>,
+    mut rng: ResMut<GameRng>,
+    mut log: ResMut<GameLog>,
+    locale: Res<Locale>,
+    match_settings: Res<MatchSettings>,
 ) {
     let player = state.player;
+    let name = |player: usize| ("name", match_settings.player(player).name.clone());
+    // Chips are only discarded once the events are handled, so every card of
+    // this run sees them in `chips_on_table` and must skip those taken before.
+    let mut claimed_chips: HashSet<Entity> = HashSet::new();
+    // Chips sent towards the sales area by earlier vehicles of this run.
+    let mut shipped = 0;
 
     // Apply Cards in Play Area
-    for (entity, card, _) in cards_on_table
+    for (entity, card, card_on_table, script_run) in cards_on_table
         .iter()
-        .filter(|(_, _, card_on_table)| card_on_table.player == player)
+        .filter(|(_, _, card_on_table, _)| card_on_table.player == player)
     {
+        // Scripts run once per phase, next to the built-in effect.
+        if let Some(source) = card.data.script.as_ref() {
+            let ran = script_run.is_some_and(|run| {
+                run.turn_number == state.turn_number && run.phase == state.phase
+            });
+            if !ran && script::phase_hook(state.phase).is_some() {
+                commands.entity(entity).insert(ScriptRun {
+                    turn_number: state.turn_number,
+                    phase: state.phase,
+                });
+                match script::run(source, &state, &chips_on_table) {
+                    Ok(actions) => script::apply(
+                        actions,
+                        &mut state,
+                        &chips_on_table,
+                        &mut claimed_chips,
+                        &mut ew_drop_chip,
+                        &mut ew_move_chip,
+                        &mut log,
+                    ),
+                    Err(error) => {
+                        warn!("Script of {:?} failed: {}", card.data.card_type, error);
+                        let text = locale.format(
+                            "log-misfired",
+                            &[("card", locale.card_name(card.data.card_type))],
+                        );
+                        log.push(state.turn_number, text);
+                    }
+                }
+            }
+        }
+
         match state.phase {
             TurnPhase::ApplyProductionCards => match card.data.card_type {
                 CardType::Cocaine | CardType::Cannabis => {
@@ -347,6 +993,8 @@ pub fn apply_card_effects(
                         match effect.effect_type {
                             EffectType::Attack => acc + 1,
                             EffectType::Drought => acc + 1,
+                            EffectType::BumperHarvest => acc - BUMPER_HARVEST_BONUS,
+                            _ => acc,
                         }
                     });
 
@@ -365,193 +1013,234 @@ pub fn apply_card_effects(
 
                     ew_place_card_off_table.send(PlaceCardOffTable {
                         card_entity: entity,
-                        deck_marker: 1,
+                        deck_marker: state.playing_deck(player),
                     });
                 }
                 _ => {}
             },
-            TurnPhase::ApplyTransportationCards => match card.data.card_type {
-                CardType::Truck | CardType::Train => {
-                    let mut cannabis_chips_on_table = chips_on_table
-                        .iter()
-                        .filter(|(_, _, chip, area)| {
-                            chip.data == ChipType::Cannabis
-                                && area.marker == 1
-                                && area.player == player
-                                && chip.turn_activation_1 < state.turn_number
-                        })
-                        .collect::<Vec<_>>();
-                    cannabis_chips_on_table.sort_by(|(_, t1, _, _), (_, t2, _, _)| {
-                        t1.translation.z.partial_cmp(&t2.translation.z).unwrap()
-                    });
-
-                    let mut cocaine_chips_on_table = chips_on_table
-                        .iter()
-                        .filter(|(_, _, chip, area)| {
-                            chip.data == ChipType::Cocaine
-                                && area.marker == 1
-                                && area.player == player
-                                && chip.turn_activation_1 < state.turn_number
-                        })
-                        .collect::<Vec<_>>();
-
-                    cocaine_chips_on_table.sort_by(|(_, t1, _, _), (_, t2, _, _)| {
-                        t1.translation.z.partial_cmp(&t2.translation.z).unwrap()
-                    });
-
-                    let mut entities_to_move: Vec<Entity> = vec![];
-                    let mut chip_value = match card.data.card_type {
-                        CardType::Truck => 20,
-                        CardType::Train => 50,
-                        _ => 0,
+            TurnPhase::ApplyTransportationCards => {
+                if let Some(vehicle) = card.data.card_type.vehicle() {
+                    let cargo = state.get_cargo(player);
+                    let loadable = |chip_type: ChipType| {
+                        let mut chips = chips_on_table
+                            .iter()
+                            .filter(|(entity, _, chip, area)| {
+                                !claimed_chips.contains(entity)
+                                    && chip.data == chip_type
+                                    && area.marker == 1
+                                    && area.player == player
+                                    && chip.turn_activation_1 < state.turn_number
+                            })
+                            .map(|(entity, transform, chip, _)| {
+                                (entity, transform.translation.z, chip.data)
+                            })
+                            .collect::<Vec<_>>();
+                        chips.sort_by(|(_, z1, _), (_, z2, _)| z1.partial_cmp(z2).unwrap());
+                        chips
                     };
+                    let cocaine_chips = loadable(ChipType::Cocaine);
+                    let cannabis_chips = loadable(ChipType::Cannabis);
+                    let chips_to_load = cargo.order(cocaine_chips, cannabis_chips);
+
+                    // Chips only leave once there is room in the sales area,
+                    // counting what is already on the road.
+                    let free_space = state.storage_capacity(player, 2).saturating_sub(
+                        chips_on_table
+                            .iter()
+                            .filter(|(_, _, _, area)| {
+                                (area.marker == 2 || area.marker == TRANSIT_AREA)
+                                    && area.player == player
+                            })
+                            .count()
+                            + shipped,
+                    );
 
-                    let common_chips =
-                        std::cmp::min(cannabis_chips_on_table.len(), cocaine_chips_on_table.len());
-
-                    for i in 0..common_chips {
-                        let cannabis_chip = cannabis_chips_on_table[i].0;
-                        let cocaine_chip = cocaine_chips_on_table[i].0;
-
-                        entities_to_move.push(cannabis_chip);
-                        entities_to_move.push(cocaine_chip);
-                    }
-
-                    if common_chips < cannabis_chips_on_table.len() {
-                        for chip in cannabis_chips_on_table.iter().skip(common_chips) {
-                            entities_to_move.push(chip.0);
+                    let mut intercepted = 0;
+                    for (chip_entity, _, chip_type) in chips_to_load
+                        .into_iter()
+                        .take(vehicle.capacity.min(free_space))
+                    {
+                        let cost = vehicle.cost(chip_type);
+                        if cost > state.get_balance(player) {
+                            break;
                         }
-                    }
-
-                    if common_chips < cocaine_chips_on_table.len() {
-                        for chip in cocaine_chips_on_table.iter().skip(common_chips) {
-                            entities_to_move.push(chip.0);
+                        state.change_balance(player, -cost);
+                        claimed_chips.insert(chip_entity);
+
+                        if vehicle.travel_turns > 0 {
+                            shipped += 1;
+                            commands.entity(chip_entity).insert(InTransit {
+                                arrival_turn: state.turn_number + vehicle.travel_turns,
+                                interception_risk: vehicle.interception_risk,
+                            });
+                            ew_move_chip.send(MoveChip {
+                                entity: chip_entity,
+                                area: TRANSIT_AREA,
+                                player,
+                            });
+                        } else if rng.gen_range(0..100) < vehicle.interception_risk {
+                            intercepted += 1;
+                            ew_discard_chip.send(DiscardChip {
+                                entity: chip_entity,
+                            });
+                        } else {
+                            shipped += 1;
+                            ew_move_chip.send(MoveChip {
+                                entity: chip_entity,
+                                area: 2,
+                                player,
+                            });
                         }
                     }
 
-                    for entity in entities_to_move {
-                        if chip_value <= 0 {
-                            break;
-                        }
-
-                        let event = MoveChip {
-                            entity,
-                            area: 2,
-                            player,
-                        };
-                        ew_move_chip.send(event);
-                        chip_value -= 2;
+                    if intercepted > 0 {
+                        let text = locale.format(
+                            "log-intercepted",
+                            &[
+                                ("count", intercepted.to_string()),
+                                ("card", locale.card_name(card.data.card_type)),
+                                name(player),
+                            ],
+                        );
+                        log.push(state.turn_number, text);
                     }
 
                     ew_place_card_off_table.send(PlaceCardOffTable {
                         card_entity: entity,
-                        deck_marker: 1,
+                        deck_marker: state.playing_deck(player),
                     });
                 }
-                _ => {}
-            },
-            TurnPhase::ApplySalesCards => match card.data.card_type {
-                CardType::Export | CardType::LocalMarket => {
-                    let mut cannabis_chips_on_table = chips_on_table
-                        .iter()
-                        .filter(|(_, _, chip, area)| {
-                            chip.data == ChipType::Cannabis
-                                && area.marker == 2
-                                && area.player == player
-                                && chip.turn_activation_2 < state.turn_number
-                                && chip.turn_activation_2 != 0
-                        })
-                        .collect::<Vec<_>>();
-                    cannabis_chips_on_table.sort_by(|(_, t1, _, _), (_, t2, _, _)| {
-                        t2.translation.z.partial_cmp(&t1.translation.z).unwrap()
-                    });
-
-                    let mut cocaine_chips_on_table = chips_on_table
-                        .iter()
-                        .filter(|(_, _, chip, area)| {
-                            chip.data == ChipType::Cocaine
-                                && area.marker == 2
-                                && area.player == player
-                                && chip.turn_activation_2 < state.turn_number
-                                && chip.turn_activation_2 != 0
-                        })
-                        .collect::<Vec<_>>();
-
-                    cocaine_chips_on_table.sort_by(|(_, t1, _, _), (_, t2, _, _)| {
-                        t2.translation.z.partial_cmp(&t1.translation.z).unwrap()
-                    });
-
-                    let mut entities_to_discard: Vec<Entity> = vec![];
-                    let mut chip_value = match card.data.card_type {
-                        CardType::Export => 50,
-                        CardType::LocalMarket => 20,
-                        _ => 0,
-                    };
-
-                    let common_chips =
-                        std::cmp::min(cannabis_chips_on_table.len(), cocaine_chips_on_table.len());
-
-                    for i in 0..common_chips {
-                        let cannabis_chip = cannabis_chips_on_table[i].0;
-                        let cocaine_chip = cocaine_chips_on_table[i].0;
-
-                        entities_to_discard.push(cannabis_chip);
-                        entities_to_discard.push(cocaine_chip);
+            }
+            TurnPhase::ApplySalesCards => {
+                if let Some(market) = card.data.card_type.market() {
+                    if market == Market::Export
+                        && state.has_effect(player, EffectType::BorderClosure)
+                    {
+                        let text = locale.format("log-borders-closed", &[name(player)]);
+                        log.push(state.turn_number, text);
+                        ew_place_card_off_table.send(PlaceCardOffTable {
+                            card_entity: entity,
+                            deck_marker: state.playing_deck(player),
+                        });
+                        continue;
                     }
 
-                    if common_chips < cannabis_chips_on_table.len() {
-                        for i in common_chips..cannabis_chips_on_table.len() {
-                            entities_to_discard.push(cannabis_chips_on_table[i].0);
+                    // Read up front, the state changes while selling.
+                    let crashed = state.has_effect(player, EffectType::MarketCrash);
+                    let price = |chip_type: ChipType| {
+                        if crashed {
+                            market.price(chip_type) / 2
+                        } else {
+                            market.price(chip_type)
                         }
-                    }
-
-                    if common_chips < cocaine_chips_on_table.len() {
-                        for i in common_chips..cocaine_chips_on_table.len() {
-                            entities_to_discard.push(cocaine_chips_on_table[i].0);
+                    };
+                    let sellable = |chip_type: ChipType| {
+                        let mut chips = chips_on_table
+                            .iter()
+                            .filter(|(entity, _, chip, area)| {
+                                !claimed_chips.contains(entity)
+                                    && chip.data == chip_type
+                                    && area.marker == 2
+                                    && area.player == player
+                                    && chip.turn_activation_2 < state.turn_number
+                                    && chip.turn_activation_2 != 0
+                            })
+                            .map(|(entity, transform, chip, _)| {
+                                (entity, transform.translation.z, chip.data)
+                            })
+                            .collect::<Vec<_>>();
+                        chips.sort_by(|(_, z1, _), (_, z2, _)| z2.partial_cmp(z1).unwrap());
+                        chips
+                    };
+                    let chips_to_sell = Cargo::Mixed
+                        .order(sellable(ChipType::Cocaine), sellable(ChipType::Cannabis));
+
+                    let mut sold = 0;
+                    let mut seized = 0;
+                    let mut earned = 0;
+                    for (chip_entity, _, chip_type) in chips_to_sell {
+                        // Chips beyond this turn's demand wait in the sales area.
+                        if state.market_demand_left(market, chip_type) == 0 {
+                            continue;
                         }
-                    }
-
-                    for entity in entities_to_discard {
-                        if chip_value <= 0 {
-                            break;
+                        claimed_chips.insert(chip_entity);
+                        state.record_sale(market, chip_type);
+                        ew_discard_chip.send(DiscardChip {
+                            entity: chip_entity,
+                        });
+
+                        // Selling on the street draws more attention than exporting.
+                        state.add_heat(player, market.heat_per_chip());
+
+                        if rng.gen_range(0..100) < market.seizure_risk() {
+                            seized += 1;
+                            continue;
                         }
+                        sold += 1;
+                        earned += price(chip_type);
+                    }
 
-                        let event = DiscardChip { entity };
-
-                        ew_discard_chip.send(event);
-                        chip_value -= 2;
+                    let payment_delay = market.payment_delay();
+                    if payment_delay > 0 {
+                        state.add_pending_payment(player, earned, payment_delay);
+                    } else {
+                        state.change_balance(player, earned);
+                    }
 
-                        let cost: i32 = match card.data.card_type {
-                            CardType::Cocaine => 800,
-                            CardType::Cannabis => 400,
-                            _ => 0,
+                    if sold + seized > 0 {
+                        let mut args = vec![
+                            name(player),
+                            ("count", sold.to_string()),
+                            ("market", locale.name_of("market", market)),
+                            ("amount", locale.money(earned)),
+                        ];
+                        let key = if payment_delay > 0 {
+                            args.push(("turn", (state.turn_number + payment_delay).to_string()));
+                            "log-sold-paid-later"
+                        } else {
+                            "log-sold"
                         };
-
-                        state.change_balance(player, cost);
+                        let text = locale.format(key, &args);
+                        log.push(state.turn_number, text);
+                    }
+                    if seized > 0 {
+                        let text = locale
+                            .format("log-seized", &[("count", seized.to_string()), name(player)]);
+                        log.push(state.turn_number, text);
                     }
 
                     ew_place_card_off_table.send(PlaceCardOffTable {
                         card_entity: entity,
-                        deck_marker: 1,
+                        deck_marker: state.playing_deck(player),
                     });
                 }
-                _ => {}
-            },
+            }
             TurnPhase::ApplyActionCards => match card.data.card_type {
+                CardType::Warehouse => {
+                    state.upgrade_storage(player, STORAGE_UPGRADE);
+
+                    ew_place_card_off_table.send(PlaceCardOffTable {
+                        card_entity: entity,
+                        deck_marker: state.playing_deck(player),
+                    });
+                }
                 CardType::Attack => {
-                    state.add_effect(
-                        EffectType::Attack,
-                        2,
-                        match player {
-                            1 => 2,
-                            2 => 1,
-                            _ => 1,
-                        },
-                    );
+                    let target = state.next_player(player);
+                    state.add_effect(EffectType::Attack, 2, target);
+                    state.add_heat(player, HEAT_PER_ATTACK);
 
                     ew_place_card_off_table.send(PlaceCardOffTable {
                         card_entity: entity,
-                        deck_marker: 1,
+                        deck_marker: state.playing_deck(player),
+                    });
+                }
+                // Scripted cards stay for every phase they act in and leave
+                // after the last one; drawn events are cleared at the end of
+                // the round instead.
+                CardType::Scripted if card_on_table.marker <= 5 => {
+                    ew_place_card_off_table.send(PlaceCardOffTable {
+                        card_entity: entity,
+                        deck_marker: state.playing_deck(player),
                     });
                 }
                 _ => {}
@@ -576,16 +1265,28 @@ pub fn apply_card_effects(
                 event_cards.sort_by(|(_, t1, _, _), (_, t2, _, _)| {
                     t2.translation.z.partial_cmp(&t1.translation.z).unwrap()
                 });
-                let entity = event_cards[0].0;
-                commands
-                    .entity(entity)
-                    .insert(ActiveEventCard { player })
-                    .remove::<Deck>();
-                ew_place_card_on_table.send(PlaceCardOnTable {
-                    card_entity: event_cards[0].0,
-                    marker: 6,
-                    player,
-                });
+
+                // The hotter the player, the likelier the police shows up;
+                // otherwise police cards are skipped in favour of the next one.
+                let police_shows_up = rng.gen_range(0..MAX_HEAT) < state.get_heat(player);
+                let is_police =
+                    |card: &Card<Kard>| matches!(card.data.card_type, CardType::PoliceRaid);
+                let drawn = event_cards
+                    .iter()
+                    .find(|(_, _, card, _)| is_police(card) == police_shows_up)
+                    .or(event_cards.first());
+
+                if let Some(&(entity, _, _, _)) = drawn {
+                    commands
+                        .entity(entity)
+                        .insert(ActiveEventCard { player })
+                        .remove::<Deck>();
+                    ew_place_card_on_table.send(PlaceCardOnTable {
+                        card_entity: entity,
+                        marker: 6,
+                        player,
+                    });
+                }
             }
         }
         TurnPhase::End => {
@@ -607,10 +1308,85 @@ pub fn apply_card_effects(
 
             for (_, card, _) in event_cards {
                 let card_type = card.data.card_type;
+                // Global events affect every seat, the others only the player
+                // who drew them.
+                let targets = if card_type.is_global_event() {
+                    (1..=state.num_players).collect::<Vec<_>>()
+                } else {
+                    vec![player]
+                };
                 match card_type {
                     CardType::Drought => {
                         state.add_effect(EffectType::Drought, 3, player);
                     }
+                    CardType::BumperHarvest => {
+                        state.add_effect(EffectType::BumperHarvest, 1, player);
+                        let text = locale.format("log-bumper-harvest", &[name(player)]);
+                        log.push(state.turn_number, text);
+                    }
+                    CardType::MarketCrash => {
+                        // Lasts until the drawer's next turn.
+                        for target in targets {
+                            state.add_effect(EffectType::MarketCrash, 1, target);
+                        }
+                        log.push(state.turn_number, locale.get("log-market-crash"));
+                    }
+                    CardType::BorderClosure => {
+                        for target in targets {
+                            state.add_effect(EffectType::BorderClosure, 1, target);
+                        }
+                        log.push(state.turn_number, locale.get("log-border-closure"));
+                    }
+                    CardType::PoliceCrackdown => {
+                        for target in targets {
+                            let fine = state.get_heat(target) * CRACKDOWN_FINE_PER_HEAT;
+                            if fine > 0 {
+                                state.draw_bank(target, fine);
+                                let text = locale.format(
+                                    "log-crackdown",
+                                    &[name(target), ("amount", locale.money(fine))],
+                                );
+                                log.push(state.turn_number, text);
+                            }
+                        }
+                    }
+                    CardType::Informant => {
+                        // The informant tells the player what the next seat
+                        // holds. The log is public, so it only says who was
+                        // spied on.
+                        let target = state.next_player(player);
+                        let cards = cards_in_hand
+                            .iter()
+                            .filter(|(_, hand)| hand.player == target)
+                            .map(|(card, _)| card.data.card_type)
+                            .collect();
+                        ew_informant_report.send(InformantReport {
+                            player,
+                            target,
+                            cards,
+                        });
+                        let text = locale.format(
+                            "log-informant",
+                            &[
+                                name(player),
+                                ("target", match_settings.player(target).name.clone()),
+                            ],
+                        );
+                        log.push(state.turn_number, text);
+                    }
+                    CardType::PoliceRaid => {
+                        // One chip is confiscated per 10 points of heat and the
+                        // raid cools things down for a while.
+                        let heat = state.get_heat(player);
+                        chips_on_table
+                            .iter()
+                            .filter(|(_, _, _, area)| area.player == player)
+                            .take((heat / 10) as usize)
+                            .for_each(|(entity, _, _, _)| {
+                                ew_discard_chip.send(DiscardChip { entity });
+                            });
+                        state.add_heat(player, -heat / 2);
+                    }
 
                     _ => {}
                 }
@@ -636,13 +1412,23 @@ pub fn apply_card_effects(
 }
 
 pub fn handle_next_phase(
+    mut commands: Commands,
     mut er_next_phase: EventReader<AdvancePhase>,
     mut ew_align_cards_in_hand: EventWriter<AlignCardsInHand>,
     mut ew_align_chips_on_table: EventWriter<AlignChipsOnTable<ChipType>>,
+    mut ew_discard_chip: EventWriter<DiscardChip>,
+    mut ew_place_card_off_table: EventWriter<PlaceCardOffTable>,
     mut game_state: ResMut<GameState>,
     mut ew_switch_player: EventWriter<SwitchPlayer>,
     mut phase_timer: ResMut<PhaseTimer>,
+    mut rng: ResMut<GameRng>,
+    mut log: ResMut<GameLog>,
+    mut ew_move_chip: EventWriter<MoveChip>,
+    chips_on_table: Query<(Entity, &Chip<ChipType>, &ChipArea, Option<&InTransit>)>,
+    cards_in_hand: Query<(Entity, &Hand), With<Card<Kard>>>,
     time: Res<Time>,
+    locale: Res<Locale>,
+    match_settings: Res<MatchSettings>,
 ) {
     if !phase_timer.0.finished() {
         phase_timer.0.tick(time.delta());
@@ -660,63 +1446,76 @@ pub fn handle_next_phase(
                 });
             }
 
+            TurnPhase::ApplyTransportationCards => {
+                let intercepted = deliver_chips_in_transit(
+                    &mut commands,
+                    &game_state,
+                    &mut rng,
+                    &chips_on_table,
+                    &mut ew_move_chip,
+                    &mut ew_discard_chip,
+                );
+                if intercepted > 0 {
+                    let name = &match_settings.player(game_state.player).name;
+                    let text = locale.format(
+                        "log-intercepted-in-transit",
+                        &[("count", intercepted.to_string()), ("name", name.clone())],
+                    );
+                    log.push(game_state.turn_number, text);
+                }
+            }
+
             TurnPhase::End => {
-                ew_align_chips_on_table.send(AlignChipsOnTable::<ChipType> {
-                    chip_area: ChipArea {
-                        marker: 1,
-                        player: 1,
-                    },
-                    chip_type: ChipType::Cocaine,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable::<ChipType> {
-                    chip_area: ChipArea {
-                        marker: 1,
-                        player: 1,
-                    },
-                    chip_type: ChipType::Cannabis,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable {
-                    chip_area: ChipArea {
-                        marker: 2,
-                        player: 1,
-                    },
-                    chip_type: ChipType::Cocaine,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable {
-                    chip_area: ChipArea {
-                        marker: 2,
-                        player: 1,
-                    },
-                    chip_type: ChipType::Cannabis,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable::<ChipType> {
-                    chip_area: ChipArea {
-                        marker: 1,
-                        player: 2,
-                    },
-                    chip_type: ChipType::Cocaine,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable::<ChipType> {
-                    chip_area: ChipArea {
-                        marker: 1,
-                        player: 2,
-                    },
-                    chip_type: ChipType::Cannabis,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable {
-                    chip_area: ChipArea {
-                        marker: 2,
-                        player: 2,
-                    },
-                    chip_type: ChipType::Cocaine,
-                });
-                ew_align_chips_on_table.send(AlignChipsOnTable {
-                    chip_area: ChipArea {
-                        marker: 2,
-                        player: 2,
-                    },
-                    chip_type: ChipType::Cannabis,
-                });
+                let player = game_state.player;
+                game_state.add_heat(player, -HEAT_DECAY);
+
+                let spoiled = match game_state.spoilage_turns {
+                    Some(spoilage_turns) => chips_on_table
+                        .iter()
+                        .filter(|(_, chip, area, _)| {
+                            area.player == player
+                                && area.marker == 1
+                                && chip.turn_activation_1 + spoilage_turns <= game_state.turn_number
+                        })
+                        .map(|(entity, _, _, _)| entity)
+                        .collect::<Vec<_>>(),
+                    None => vec![],
+                };
+                for &entity in spoiled.iter() {
+                    ew_discard_chip.send(DiscardChip { entity });
+                }
+                if !spoiled.is_empty() {
+                    let text =
+                        locale.format("log-spoiled", &[("count", spoiled.len().to_string())]);
+                    log.push(game_state.turn_number, text);
+                }
+
+                let unpaid = game_state.charge_interest(player);
+                if unpaid > 0 {
+                    collect_unpaid_debt(
+                        &mut commands,
+                        &mut game_state,
+                        &mut rng,
+                        player,
+                        unpaid,
+                        &chips_on_table,
+                        &spoiled,
+                        &cards_in_hand,
+                        &mut ew_discard_chip,
+                        &mut ew_place_card_off_table,
+                    );
+                }
+
+                for player in 1..=game_state.num_players {
+                    for marker in 1..=2 {
+                        for chip_type in [ChipType::Cocaine, ChipType::Cannabis] {
+                            ew_align_chips_on_table.send(AlignChipsOnTable::<ChipType> {
+                                chip_area: ChipArea { marker, player },
+                                chip_type,
+                            });
+                        }
+                    }
+                }
             }
             _ => {}
         }
@@ -734,6 +1533,96 @@ pub fn handle_next_phase(
     }
 }
 
+/// Unloads the player's vehicles that reached the sales area this turn.
+/// Every chip still risks being intercepted on the last stretch; returns how
+/// many were.
+fn deliver_chips_in_transit(
+    commands: &mut Commands,
+    game_state: &GameState,
+    rng: &mut GameRng,
+    chips_on_table: &Query<(Entity, &Chip<ChipType>, &ChipArea, Option<&InTransit>)>,
+    ew_move_chip: &mut EventWriter<MoveChip>,
+    ew_discard_chip: &mut EventWriter<DiscardChip>,
+) -> usize {
+    let player = game_state.player;
+    let mut intercepted = 0;
+    for (entity, _, _, in_transit) in chips_on_table
+        .iter()
+        .filter(|(_, _, area, _)| area.player == player)
+    {
+        let Some(in_transit) = in_transit else {
+            continue;
+        };
+        if in_transit.arrival_turn > game_state.turn_number {
+            continue;
+        }
+
+        commands.entity(entity).remove::<InTransit>();
+        if rng.gen_range(0..100) < in_transit.interception_risk {
+            intercepted += 1;
+            ew_discard_chip.send(DiscardChip { entity });
+        } else {
+            ew_move_chip.send(MoveChip {
+                entity,
+                area: 2,
+                player,
+            });
+        }
+    }
+
+    intercepted
+}
+
+/// The loan shark takes chips worth at least the unpaid interest. A player
+/// without chips has to give up a card from their hand instead.
+fn collect_unpaid_debt(
+    commands: &mut Commands,
+    game_state: &mut GameState,
+    rng: &mut GameRng,
+    player: usize,
+    unpaid: i32,
+    chips_on_table: &Query<(Entity, &Chip<ChipType>, &ChipArea, Option<&InTransit>)>,
+    // Already discarded this phase; they are worth nothing any more.
+    spoiled: &[Entity],
+    cards_in_hand: &Query<(Entity, &Hand), With<Card<Kard>>>,
+    ew_discard_chip: &mut EventWriter<DiscardChip>,
+    ew_place_card_off_table: &mut EventWriter<PlaceCardOffTable>,
+) {
+    let mut chips = chips_on_table
+        .iter()
+        .filter(|(entity, _, area, in_transit)| {
+            area.player == player && in_transit.is_none() && !spoiled.contains(entity)
+        })
+        .collect::<Vec<_>>();
+    // Goods waiting in the sales area go first, most valuable first.
+    chips.sort_by_key(|(_, chip, area, _)| (area.marker, chip.data.price()));
+
+    let mut seized = 0;
+    while seized < unpaid {
+        let Some((entity, chip, _, _)) = chips.pop() else {
+            break;
+        };
+        seized += chip.data.price();
+        ew_discard_chip.send(DiscardChip { entity });
+    }
+    game_state.seize(player, seized);
+
+    if seized == 0 {
+        let hand = cards_in_hand
+            .iter()
+            .filter(|(_, hand)| hand.player == player)
+            .map(|(entity, _)| entity)
+            .collect::<Vec<_>>();
+        if let Some(&card_entity) = hand.choose(&mut rng.0) {
+            commands.entity(card_entity).remove::<Hand>();
+            ew_place_card_off_table.send(PlaceCardOffTable {
+                card_entity,
+                deck_marker: game_state.playing_deck(player),
+            });
+        }
+    }
+}
+
 pub fn handle_drop_chip(mut er_drop_chip: EventReader<DropChip>) {
     for _drop_chip in er_drop_chip.read() {
         // println!("Dropping chip: {:?}", drop_chip.chip_type);
@@ -746,41 +1635,44 @@ pub fn handle_move_chip(
     state: Res<GameState>,
 ) {
     for move_chip in er_drop_chip.read() {
-        let (_, mut chip) = query.get_mut(move_chip.entity).unwrap();
-        chip.turn_activation_2 = state.turn_number;
+        // The chip may have been discarded in the meantime.
+        let Ok((_, mut chip)) = query.get_mut(move_chip.entity) else {
+            continue;
+        };
+        if move_chip.area == 2 {
+            chip.turn_activation_2 = state.turn_number;
+        }
     }
 }
 
+/// Hands the turn over; the camera follows in `camera::follow_current_player`.
 pub fn handle_switch_player(
     mut er_switch_player: EventReader<SwitchPlayer>,
     mut game_state: ResMut<GameState>,
-    mut query: Query<(&mut Transform, &GameCamera)>,
 ) {
     for event in er_switch_player.read() {
         game_state.player = event.player;
-
-        for (mut transform, _) in query.iter_mut() {
-            if game_state.player == 1 {
-                *transform = Transform::from_xyz(0.0, 12.0, 15.0).looking_at(Vec3::ZERO, Vec3::Y);
-            } else {
-                *transform = Transform::from_xyz(-3.0, 12.0, -15.0)
-                    .looking_at(Vec3::ZERO + Vec3::new(-3.0, 0.0, 0.0), Vec3::Y);
-            }
-        }
     }
 }
 
 pub fn check_game_over_conditions(
     mut er_advance_phase: EventReader<AdvancePhase>,
     game_state: Res<GameState>,
+    scenario: Option<Res<ActiveScenario>>,
     mut ew_game_over_event: EventWriter<GameOver>,
 ) {
+    // Scenarios bring their own objective and failure conditions.
+    if scenario.is_some() {
+        er_advance_phase.clear();
+        return;
+    }
+
     for _ in er_advance_phase.read() {
         let funds_per_player: Vec<i32> = (1..=game_state.num_players)
-            .map(|player| game_state.get_balance(player))
+            .map(|player| game_state.net_worth(player))
             .collect();
 
-        let lowest_funds = *funds_per_player.iter().min().unwrap();
+        let bankrupt = (1..=game_state.num_players).any(|player| game_state.is_bankrupt(player));
 
         let highest_funds = *funds_per_player.iter().max().unwrap();
         let index_highest_funds = funds_per_player
@@ -789,21 +1681,21 @@ pub fn check_game_over_conditions(
             .unwrap();
         let total_players = game_state.num_players;
 
-        if total_players == 2 && lowest_funds <= 0 {
+        if total_players == 2 && bankrupt {
             ew_game_over_event.send(GameOver {
                 player_won: index_highest_funds + 1,
             });
             return;
         }
 
-        if highest_funds >= 50000 {
+        if highest_funds >= game_state.winning_bank {
             ew_game_over_event.send(GameOver {
                 player_won: index_highest_funds + 1,
             });
             return;
         }
 
-        if game_state.turn_number >= 20 {
+        if game_state.turn_number >= game_state.max_turns {
             ew_game_over_event.send(GameOver {
                 player_won: index_highest_funds + 1,
             });
@@ -0,0 +1,172 @@
+//! Canonical fingerprint of the rules state, used to catch desyncs.
+//!
+//! A [`StateDigest`] gathers everything the rules depend on in a fixed order,
+//! so that two peers running the same match end up with the same checksum
+//! after every phase. When the checksums differ, [`StateDigest::diff`] names
+//! the fields that diverged.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{Card, CardOnTable, Chip, ChipArea, Deck};
+use serde::{Deserialize, Serialize};
+
+use super::cards::{
+    CardType, Cargo, ChipType, Effect, GameState, Kard, Market, PendingPayment, TurnPhase,
+};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateDigest {
+    pub turn_number: usize,
+    pub phase: TurnPhase,
+    pub player: usize,
+    pub bank: Vec<i32>,
+    pub debt: Vec<i32>,
+    pub heat: Vec<i32>,
+    pub cargo: Vec<Cargo>,
+    pub storage_capacity: Vec<[usize; 2]>,
+    pub effects: Vec<Effect>,
+    pub market_sales: Vec<(Market, ChipType, usize)>,
+    pub pending_payments: Vec<PendingPayment>,
+    /// Chips per player, area and type.
+    pub chips: Vec<(usize, usize, ChipType, usize)>,
+    /// Cards on the table per player and slot.
+    pub table: Vec<(usize, usize, CardType)>,
+    /// Card types of each deck from top to bottom; `None` for peers that
+    /// can't see the decks.
+    pub decks: Option<Vec<(usize, Vec<CardType>)>>,
+}
+
+impl StateDigest {
+    pub fn new<'a>(
+        state: &GameState,
+        chips: impl Iterator<Item = (&'a Chip<ChipType>, &'a ChipArea)>,
+        table: impl Iterator<Item = (&'a Card<Kard>, &'a CardOnTable)>,
+    ) -> Self {
+        let mut market_sales = state
+            .market_sales
+            .iter()
+            .map(|(&(market, chip_type), &sold)| (market, chip_type, sold))
+            .collect::<Vec<_>>();
+        market_sales.sort_by_key(|&(market, chip_type, _)| (market as usize, chip_type as usize));
+
+        let mut chip_counts: Vec<(usize, usize, ChipType, usize)> = vec![];
+        for (chip, area) in chips {
+            match chip_counts
+                .iter_mut()
+                .find(|(player, marker, chip_type, _)| {
+                    *player == area.player && *marker == area.marker && *chip_type == chip.data
+                }) {
+                Some((_, _, _, count)) => *count += 1,
+                None => chip_counts.push((area.player, area.marker, chip.data, 1)),
+            }
+        }
+        chip_counts
+            .sort_by_key(|&(player, marker, chip_type, _)| (player, marker, chip_type as usize));
+
+        let mut table = table
+            .map(|(card, card_on_table)| {
+                (
+                    card_on_table.player,
+                    card_on_table.marker,
+                    card.data.card_type,
+                )
+            })
+            .collect::<Vec<_>>();
+        table.sort_by_key(|&(player, marker, _)| (player, marker));
+
+        Self {
+            turn_number: state.turn_number,
+            phase: state.phase,
+            player: state.player,
+            bank: state.bank.clone(),
+            debt: state.debt.clone(),
+            heat: state.heat.clone(),
+            cargo: state.cargo.clone(),
+            storage_capacity: state.storage_capacity.clone(),
+            effects: state.effects.clone(),
+            market_sales,
+            pending_payments: state.pending_payments.clone(),
+            chips: chip_counts,
+            table,
+            decks: None,
+        }
+    }
+
+    /// Adds the deck order, for peers holding the decks.
+    pub fn with_decks<'a>(
+        mut self,
+        cards: impl Iterator<Item = (&'a Card<Kard>, &'a Deck, &'a Transform)>,
+    ) -> Self {
+        let mut cards = cards.collect::<Vec<_>>();
+        cards.sort_by(|(_, d1, t1), (_, d2, t2)| {
+            d1.marker
+                .cmp(&d2.marker)
+                .then(t2.translation.z.total_cmp(&t1.translation.z))
+        });
+
+        let mut decks: Vec<(usize, Vec<CardType>)> = vec![];
+        for (card, deck, _) in cards {
+            match decks.last_mut() {
+                Some((marker, card_types)) if *marker == deck.marker => {
+                    card_types.push(card.data.card_type)
+                }
+                _ => decks.push((deck.marker, vec![card.data.card_type])),
+            }
+        }
+        self.decks = Some(decks);
+        self
+    }
+
+    /// FNV-1a hash of the serialized digest, identical on every platform.
+    pub fn checksum(&self) -> u64 {
+        let serialized = ron::to_string(self).unwrap_or_default();
+        serialized.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+    }
+
+    /// Describes every field that differs from `other`. Decks are only
+    /// compared when both sides know them.
+    pub fn diff(&self, other: &Self) -> Vec<String> {
+        let mut differences = vec![];
+        macro_rules! compare {
+            ($($field:ident),*) => {
+                $(
+                    if self.$field != other.$field {
+                        differences.push(format!(
+                            "{}: {:?} != {:?}",
+                            stringify!($field),
+                            self.$field,
+                            other.$field
+                        ));
+                    }
+                )*
+            };
+        }
+        compare!(
+            turn_number,
+            phase,
+            player,
+            bank,
+            debt,
+            heat,
+            cargo,
+            storage_capacity,
+            effects,
+            market_sales,
+            pending_payments,
+            chips,
+            table
+        );
+        if let (Some(decks), Some(other_decks)) = (&self.decks, &other.decks) {
+            if decks != other_decks {
+                differences.push(format!("decks: {:?} != {:?}", decks, other_decks));
+            }
+        }
+        differences
+    }
+
+    /// Where the digest was taken, e.g. "turn 3, ApplySalesCards".
+    pub fn describe(&self) -> String {
+        format!("turn {}, {:?}", self.turn_number, self.phase)
+    }
+}
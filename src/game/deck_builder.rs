@@ -0,0 +1,297 @@
+//! Decks players assemble from the card pool before a match.
+//!
+//! Every card of the pool costs points: a deck has to stay within
+//! [`DECK_BUDGET`], hold at least [`MIN_DECK_SIZE`] cards and respect each
+//! card's minimum and maximum copies. Decks are kept as named files under
+//! `decks/`.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    cards::{load_custom_deck, load_playing_deck, CardType, Kard},
+    locale::Locale,
+};
+
+/// Points a deck can spend on cards.
+pub const DECK_BUDGET: u32 = 60;
+pub const MIN_DECK_SIZE: usize = 10;
+pub const DECK_NAME_MAX_LENGTH: usize = 16;
+
+/// Where decks are saved, one `<name>.deck.ron` file each.
+#[cfg(not(target_family = "wasm"))]
+const DECKS_DIR: &str = "decks";
+#[cfg(not(target_family = "wasm"))]
+const DECK_EXTENSION: &str = ".deck.ron";
+
+/// Something keeping a deck from being played.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeckProblem {
+    NoName,
+    OverBudget(u32),
+    TooSmall,
+    NotInPool(CardType),
+    NoCopies(CardType),
+    TooFew(CardType, usize),
+    TooMany(CardType, usize),
+}
+
+impl DeckProblem {
+    pub fn describe(&self, locale: &Locale) -> String {
+        let card = |card_type: &CardType| ("card", locale.card_name(*card_type));
+        match self {
+            DeckProblem::NoName => locale.get("deck-problem-no-name"),
+            DeckProblem::OverBudget(points) => locale.format(
+                "deck-problem-over-budget",
+                &[("points", points.to_string())],
+            ),
+            DeckProblem::TooSmall => locale.format(
+                "deck-problem-too-small",
+                &[("count", MIN_DECK_SIZE.to_string())],
+            ),
+            DeckProblem::NotInPool(card_type) => {
+                locale.format("deck-problem-not-in-pool", &[card(card_type)])
+            }
+            DeckProblem::NoCopies(card_type) => {
+                locale.format("deck-problem-no-copies", &[card(card_type)])
+            }
+            DeckProblem::TooFew(card_type, count) => locale.format(
+                "deck-problem-too-few",
+                &[card(card_type), ("count", count.to_string())],
+            ),
+            DeckProblem::TooMany(card_type, count) => locale.format(
+                "deck-problem-too-many",
+                &[card(card_type), ("count", count.to_string())],
+            ),
+        }
+    }
+}
+
+/// A card players can put in their deck.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolCard {
+    pub card_type: CardType,
+    pub cost: u32,
+    pub min_copies: usize,
+    pub max_copies: usize,
+}
+
+/// Cards of the playing deck with what they cost. Every deck needs a way to
+/// move and sell its goods.
+pub const CARD_POOL: [PoolCard; 8] = [
+    PoolCard {
+        card_type: CardType::Cocaine,
+        cost: 6,
+        min_copies: 0,
+        max_copies: 4,
+    },
+    PoolCard {
+        card_type: CardType::Cannabis,
+        cost: 4,
+        min_copies: 0,
+        max_copies: 4,
+    },
+    PoolCard {
+        card_type: CardType::Truck,
+        cost: 3,
+        min_copies: 1,
+        max_copies: 4,
+    },
+    PoolCard {
+        card_type: CardType::Train,
+        cost: 5,
+        min_copies: 0,
+        max_copies: 3,
+    },
+    PoolCard {
+        card_type: CardType::LocalMarket,
+        cost: 2,
+        min_copies: 1,
+        max_copies: 4,
+    },
+    PoolCard {
+        card_type: CardType::Export,
+        cost: 5,
+        min_copies: 0,
+        max_copies: 3,
+    },
+    PoolCard {
+        card_type: CardType::Attack,
+        cost: 4,
+        min_copies: 0,
+        max_copies: 3,
+    },
+    PoolCard {
+        card_type: CardType::Warehouse,
+        cost: 5,
+        min_copies: 0,
+        max_copies: 2,
+    },
+];
+
+pub fn pool_card(card_type: CardType) -> Option<&'static PoolCard> {
+    CARD_POOL.iter().find(|card| card.card_type == card_type)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeckList {
+    pub name: String,
+    pub cards: Vec<(CardType, usize)>,
+}
+
+impl DeckList {
+    /// The cards every player gets in a regular match.
+    pub fn standard() -> Self {
+        let mut deck = Self {
+            name: "Standard".to_string(),
+            cards: vec![],
+        };
+        for kard in load_playing_deck(1) {
+            let copies = deck.copies(kard.card_type);
+            deck.set_copies(kard.card_type, copies + 1);
+        }
+        deck
+    }
+
+    pub fn copies(&self, card_type: CardType) -> usize {
+        self.cards
+            .iter()
+            .find(|(other, _)| *other == card_type)
+            .map_or(0, |(_, copies)| *copies)
+    }
+
+    /// Sets the copies of a card, within what the pool allows.
+    pub fn set_copies(&mut self, card_type: CardType, copies: usize) {
+        let Some(pool_card) = pool_card(card_type) else {
+            return;
+        };
+        let copies = copies.min(pool_card.max_copies);
+        self.cards.retain(|(other, _)| *other != card_type);
+        if copies > 0 {
+            self.cards.push((card_type, copies));
+        }
+        // Keep the pool order so that saved files read the same way.
+        self.cards.sort_by_key(|(card_type, _)| {
+            CARD_POOL
+                .iter()
+                .position(|card| card.card_type == *card_type)
+        });
+    }
+
+    pub fn size(&self) -> usize {
+        self.cards.iter().map(|(_, copies)| copies).sum()
+    }
+
+    pub fn cost(&self) -> u32 {
+        self.cards
+            .iter()
+            .filter_map(|&(card_type, copies)| {
+                pool_card(card_type).map(|card| card.cost * copies as u32)
+            })
+            .sum()
+    }
+
+    /// Everything keeping the deck from being played, empty for a legal deck.
+    pub fn problems(&self) -> Vec<DeckProblem> {
+        let mut problems = vec![];
+        if self.name.trim().is_empty() {
+            problems.push(DeckProblem::NoName);
+        }
+        if self.cost() > DECK_BUDGET {
+            problems.push(DeckProblem::OverBudget(self.cost() - DECK_BUDGET));
+        }
+        if self.size() < MIN_DECK_SIZE {
+            problems.push(DeckProblem::TooSmall);
+        }
+        for &(card_type, copies) in &self.cards {
+            if pool_card(card_type).is_none() {
+                problems.push(DeckProblem::NotInPool(card_type));
+            } else if copies == 0 {
+                problems.push(DeckProblem::NoCopies(card_type));
+            }
+        }
+        for card in &CARD_POOL {
+            let copies = self.copies(card.card_type);
+            if copies < card.min_copies {
+                problems.push(DeckProblem::TooFew(card.card_type, card.min_copies));
+            } else if copies > card.max_copies {
+                problems.push(DeckProblem::TooMany(card.card_type, card.max_copies));
+            }
+        }
+        problems
+    }
+
+    pub fn is_legal(&self) -> bool {
+        self.problems().is_empty()
+    }
+
+    pub fn kards(&self) -> Vec<Kard> {
+        load_custom_deck(&self.cards)
+    }
+
+    /// Saves the deck under its name, replacing any deck of the same name.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn save(&self) -> Result<String, String> {
+        let path = std::path::Path::new(DECKS_DIR).join(file_name(&self.name));
+        let deck =
+            ron::ser::to_string_pretty(self, default()).map_err(|error| error.to_string())?;
+        std::fs::create_dir_all(DECKS_DIR)
+            .and_then(|_| std::fs::write(&path, deck))
+            .map_err(|error| error.to_string())?;
+        Ok(path.display().to_string())
+    }
+
+    /// Decks aren't persisted on the web build.
+    #[cfg(target_family = "wasm")]
+    pub fn save(&self) -> Result<String, String> {
+        Err("Decks can't be saved in the browser".to_string())
+    }
+
+    /// Every saved deck, by name. Unreadable files are skipped with a warning.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn load_all() -> Vec<DeckList> {
+        let Ok(entries) = std::fs::read_dir(DECKS_DIR) else {
+            return vec![];
+        };
+        let mut decks = entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.to_string_lossy().ends_with(DECK_EXTENSION))
+            .filter_map(|path| {
+                let deck = std::fs::read_to_string(&path)
+                    .map_err(|error| error.to_string())
+                    .and_then(|deck| {
+                        ron::from_str::<DeckList>(&deck).map_err(|error| error.to_string())
+                    });
+                if let Err(error) = &deck {
+                    warn!("Could not load deck {}: {}", path.display(), error);
+                }
+                deck.ok()
+            })
+            .collect::<Vec<_>>();
+        decks.sort_by(|d1, d2| d1.name.cmp(&d2.name));
+        decks
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn load_all() -> Vec<DeckList> {
+        vec![]
+    }
+}
+
+/// File name for a deck, keeping only characters safe on every platform.
+#[cfg(not(target_family = "wasm"))]
+fn file_name(name: &str) -> String {
+    let stem = name
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("{}{}", stem, DECK_EXTENSION)
+}
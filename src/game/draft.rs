@@ -0,0 +1,162 @@
+//! Draft variant: players build their decks at the table before the first
+//! turn.
+//!
+//! Every round each player opens a pack drawn from the card pool, keeps one
+//! card and passes the rest, until the packs are empty. Packs go left in odd
+//! rounds and right in even ones. The cards a player kept become their
+//! personal deck.
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+
+use super::{
+    cards::CardType,
+    deck_builder::{DeckList, CARD_POOL},
+};
+
+pub const PACK_SIZE: usize = 5;
+pub const ROUNDS: usize = 3;
+
+/// Draft in progress, present on the draft screen only.
+#[derive(Resource, Debug)]
+pub struct Draft {
+    /// Pack in front of each seat at the start of the round.
+    packs: Vec<Vec<CardType>>,
+    picks: Vec<Vec<CardType>>,
+    round: usize,
+    /// How many times the packs have been passed this round.
+    passes: usize,
+    /// Seat picking next; every seat picks once before the packs move on.
+    picker: usize,
+}
+
+impl Draft {
+    pub fn new(num_players: usize, rng: &mut impl Rng) -> Self {
+        let mut draft = Self {
+            packs: vec![],
+            picks: vec![vec![]; num_players],
+            round: 0,
+            passes: 0,
+            picker: 1,
+        };
+        draft.open_packs(rng);
+        draft
+    }
+
+    fn num_players(&self) -> usize {
+        self.picks.len()
+    }
+
+    /// Cards are drawn with the weight of how many copies a deck may hold.
+    fn open_packs(&mut self, rng: &mut impl Rng) {
+        self.packs = (0..self.num_players())
+            .map(|_| {
+                (0..PACK_SIZE)
+                    .filter_map(|_| {
+                        CARD_POOL
+                            .choose_weighted(rng, |card| card.max_copies)
+                            .ok()
+                            .map(|card| card.card_type)
+                    })
+                    .collect()
+            })
+            .collect();
+        self.round += 1;
+        self.passes = 0;
+    }
+
+    pub fn round(&self) -> usize {
+        self.round
+    }
+
+    pub fn picker(&self) -> usize {
+        self.picker
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.round > ROUNDS
+    }
+
+    fn pack_index(&self, player: usize) -> usize {
+        let num_players = self.num_players();
+        let passes = self.passes % num_players;
+        if self.round % 2 == 1 {
+            (player - 1 + passes) % num_players
+        } else {
+            (player - 1 + num_players - passes) % num_players
+        }
+    }
+
+    /// The pack in front of `player`.
+    pub fn pack(&self, player: usize) -> &[CardType] {
+        &self.packs[self.pack_index(player)]
+    }
+
+    /// Cards `player` kept so far.
+    pub fn picks(&self, player: usize) -> &[CardType] {
+        &self.picks[player - 1]
+    }
+
+    /// The picker keeps a card of their pack; the packs move on once every
+    /// seat has picked.
+    pub fn pick(&mut self, index: usize, rng: &mut impl Rng) {
+        if self.is_finished() {
+            return;
+        }
+        let pack_index = self.pack_index(self.picker);
+        let pack = &mut self.packs[pack_index];
+        if index >= pack.len() {
+            return;
+        }
+        let card_type = pack.remove(index);
+        self.picks[self.picker - 1].push(card_type);
+
+        if self.picker < self.num_players() {
+            self.picker += 1;
+            return;
+        }
+        self.picker = 1;
+        self.passes += 1;
+        if self.packs.iter().all(|pack| pack.is_empty()) {
+            if self.round < ROUNDS {
+                self.open_packs(rng);
+            } else {
+                self.round += 1;
+            }
+        }
+    }
+
+    /// What the computer keeps: the card it is furthest short of compared
+    /// with the standard deck, the more expensive one on ties.
+    pub fn ai_pick(&self) -> usize {
+        let standard = DeckList::standard();
+        let picks = self.picks(self.picker);
+        self.pack(self.picker)
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &card_type)| {
+                let kept = picks.iter().filter(|&&kept| kept == card_type).count() as i32;
+                let cost = CARD_POOL
+                    .iter()
+                    .find(|card| card.card_type == card_type)
+                    .map_or(0, |card| card.cost);
+                (standard.copies(card_type) as i32 - kept, cost)
+            })
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// The deck made of the cards `player` kept.
+    pub fn deck(&self, player: usize) -> DeckList {
+        let mut cards: Vec<(CardType, usize)> = vec![];
+        for &card_type in self.picks(player) {
+            match cards.iter_mut().find(|(other, _)| *other == card_type) {
+                Some((_, copies)) => *copies += 1,
+                None => cards.push((card_type, 1)),
+            }
+        }
+        DeckList {
+            name: "Draft".to_string(),
+            cards,
+        }
+    }
+}
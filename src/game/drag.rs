@@ -0,0 +1,313 @@
+//! Dragging cards from the hand onto a chosen play slot.
+//!
+//! While cards are being placed, the current player may drag a card from
+//! their hand onto any free slot, move a played card to another slot (trading
+//! places with the card already there) or drag it back to the hand for a
+//! refund. A press that doesn't move is still a regular click, which places
+//! the card in the next free slot.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{
+    events::{AlignCardsInHand, CardPress, PlaceCardOnTable},
+    Card, CardOnTable, Hand, HandArea, PlayArea,
+};
+
+use super::{
+    cards::{GameState, Kard, TurnPhase, TABLE_SLOTS},
+    preview::HoveredCard,
+    settings::MatchSettings,
+    spawn::ui::handle_card_press,
+    spectator::Spectator,
+    tutorial::Tutorial,
+};
+use crate::{net::client::NetClient, screen::Screen, GameCamera};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CardDrag>();
+    // The press is held back from `handle_card_press` until it is known
+    // whether it was a click or a drag.
+    app.add_systems(
+        Update,
+        (
+            (start_drag, move_dragged_card, highlight_slots).before(handle_card_press),
+            finish_drag.after(handle_card_press),
+        )
+            .chain()
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Cursor travel, in pixels, that turns a press into a drag.
+const DRAG_THRESHOLD: f32 = 8.0;
+/// Height above the table at which a dragged card floats.
+const DRAG_HEIGHT: f32 = 1.0;
+/// How close to a slot or the hand a card must be dropped to land there.
+const DROP_RADIUS: f32 = 1.5;
+const SLOT_SIZE: Vec2 = Vec2::new(2.5, 3.5);
+const HIGHLIGHT_COLOR: Color = Color::srgb(0.2, 1.0, 0.4);
+
+#[derive(Clone, Copy, Debug)]
+struct Drag {
+    card: Entity,
+    player: usize,
+    /// Slot the card was picked up from, `None` for the hand.
+    from: Option<usize>,
+    press_position: Vec2,
+    moving: bool,
+}
+
+/// Card held by the mouse, if any.
+#[derive(Resource, Default)]
+pub struct CardDrag {
+    drag: Option<Drag>,
+    /// Hand to realign once the card sent back to it is in place.
+    realign_hand: Option<usize>,
+}
+
+impl CardDrag {
+    /// Whether `card` is held, so that pressing it must not place it yet.
+    pub fn holds(&self, card: Entity) -> bool {
+        self.drag.is_some_and(|drag| drag.card == card)
+    }
+}
+
+fn cursor_position(windows: &Query<&Window>) -> Option<Vec2> {
+    windows.get_single().ok()?.cursor_position()
+}
+
+/// Where the cursor points at on the plane cards are dragged in.
+fn cursor_on_table(
+    windows: &Query<&Window>,
+    cameras: &Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+) -> Option<Vec3> {
+    let cursor = cursor_position(windows)?;
+    let (camera, camera_transform) = cameras.get_single().ok()?;
+    let ray = camera.viewport_to_world(camera_transform, cursor)?;
+    let distance = ray.intersect_plane(Vec3::Y * DRAG_HEIGHT, InfinitePlane3d::new(Vec3::Y))?;
+    Some(ray.get_point(distance))
+}
+
+fn start_drag(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    hovered: Res<HoveredCard>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    spectator: Option<Res<Spectator>>,
+    net: Option<Res<NetClient>>,
+    cards: Query<(Option<&Hand>, Option<&CardOnTable>), With<Card<Kard>>>,
+    mut card_drag: ResMut<CardDrag>,
+) {
+    if !mouse.just_pressed(MouseButton::Left)
+        || state.phase != TurnPhase::PlaceCardsOnTable
+        || match_settings.is_ai(state.player)
+        || spectator.is_some()
+        || net.is_some()
+    {
+        return;
+    }
+    let Some(card) = hovered.0 else {
+        return;
+    };
+    let from = match cards.get(card) {
+        Ok((Some(hand), _)) if hand.player == state.player => None,
+        Ok((_, Some(card_on_table)))
+            if card_on_table.player == state.player && card_on_table.marker <= TABLE_SLOTS =>
+        {
+            Some(card_on_table.marker)
+        }
+        _ => return,
+    };
+    let Some(press_position) = cursor_position(&windows) else {
+        return;
+    };
+    card_drag.drag = Some(Drag {
+        card,
+        player: state.player,
+        from,
+        press_position,
+        moving: false,
+    });
+}
+
+fn move_dragged_card(
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut card_drag: ResMut<CardDrag>,
+    mut transforms: Query<&mut Transform, With<Card<Kard>>>,
+) {
+    let Some(drag) = card_drag.drag.as_mut() else {
+        return;
+    };
+    if !drag.moving {
+        let Some(cursor) = cursor_position(&windows) else {
+            return;
+        };
+        if cursor.distance(drag.press_position) < DRAG_THRESHOLD {
+            return;
+        }
+        drag.moving = true;
+    }
+    let (Some(point), Ok(mut transform)) = (
+        cursor_on_table(&windows, &cameras),
+        transforms.get_mut(drag.card),
+    ) else {
+        return;
+    };
+    transform.translation = point;
+}
+
+/// Slots of `player` the held card may be dropped on.
+fn valid_slots(drag: &Drag, occupied: &[(Entity, usize)]) -> Vec<usize> {
+    (1..=TABLE_SLOTS)
+        .filter(|&marker| match drag.from {
+            // From the hand, only onto free slots.
+            None => occupied.iter().all(|&(_, other)| other != marker),
+            // On the table, anywhere else; occupied slots trade places.
+            Some(from) => marker != from,
+        })
+        .collect()
+}
+
+fn occupied_slots(
+    player: usize,
+    cards_on_table: &Query<(Entity, &CardOnTable), With<Card<Kard>>>,
+) -> Vec<(Entity, usize)> {
+    cards_on_table
+        .iter()
+        .filter(|(_, card_on_table)| card_on_table.player == player)
+        .map(|(entity, card_on_table)| (entity, card_on_table.marker))
+        .collect()
+}
+
+fn highlight_slots(
+    mut gizmos: Gizmos,
+    card_drag: Res<CardDrag>,
+    play_areas: Query<(&GlobalTransform, &PlayArea)>,
+    cards_on_table: Query<(Entity, &CardOnTable), With<Card<Kard>>>,
+) {
+    let Some(drag) = card_drag.drag.filter(|drag| drag.moving) else {
+        return;
+    };
+    let slots = valid_slots(&drag, &occupied_slots(drag.player, &cards_on_table));
+    for (transform, play_area) in &play_areas {
+        if play_area.player != drag.player || !slots.contains(&play_area.marker) {
+            continue;
+        }
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        gizmos.rect(
+            translation + Vec3::Y * 0.05,
+            rotation * Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+            SLOT_SIZE,
+            HIGHLIGHT_COLOR,
+        );
+    }
+}
+
+fn finish_drag(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut commands: Commands,
+    mut card_drag: ResMut<CardDrag>,
+    mut state: ResMut<GameState>,
+    tutorial: Option<Res<Tutorial>>,
+    cards: Query<&Card<Kard>>,
+    cards_on_table: Query<(Entity, &CardOnTable), With<Card<Kard>>>,
+    play_areas: Query<(&GlobalTransform, &PlayArea)>,
+    hand_areas: Query<(&GlobalTransform, &HandArea)>,
+    mut ew_place_card_on_table: EventWriter<PlaceCardOnTable>,
+    mut ew_align_cards_in_hand: EventWriter<AlignCardsInHand>,
+    mut ew_card_press: EventWriter<CardPress>,
+) {
+    // Sent a frame late so that the card counts as part of the hand again.
+    if let Some(player) = card_drag.realign_hand.take() {
+        ew_align_cards_in_hand.send(AlignCardsInHand { player });
+    }
+
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(drag) = card_drag.drag.take() else {
+        return;
+    };
+    // A press that didn't move is a click on the card.
+    if !drag.moving {
+        if drag.from.is_none() {
+            ew_card_press.send(CardPress {
+                card_entity: drag.card,
+            });
+        }
+        return;
+    }
+
+    let Ok(card) = cards.get(drag.card) else {
+        return;
+    };
+    let occupied = occupied_slots(drag.player, &cards_on_table);
+    let slots = valid_slots(&drag, &occupied);
+    let near = |transform: &GlobalTransform, point: Vec3| {
+        transform.translation().xz().distance(point.xz()) < DROP_RADIUS
+    };
+    let point = cursor_on_table(&windows, &cameras);
+    let slot = point.and_then(|point| {
+        play_areas
+            .iter()
+            .find(|(transform, play_area)| {
+                play_area.player == drag.player
+                    && slots.contains(&play_area.marker)
+                    && near(transform, point)
+            })
+            .map(|(_, play_area)| play_area.marker)
+    });
+    let over_hand = point.is_some_and(|point| {
+        hand_areas
+            .iter()
+            .any(|(transform, hand_area)| hand_area.player == drag.player && near(transform, point))
+    });
+    let free_play = tutorial
+        .as_ref()
+        .map_or(true, |tutorial| tutorial.allows_free_play());
+    let place = |ew: &mut EventWriter<PlaceCardOnTable>, card_entity: Entity, marker: usize| {
+        ew.send(PlaceCardOnTable {
+            card_entity,
+            marker,
+            player: drag.player,
+        });
+    };
+
+    match (drag.from, slot) {
+        (None, Some(marker))
+            if card.data.price <= state.get_balance(drag.player)
+                && !tutorial
+                    .as_ref()
+                    .is_some_and(|tutorial| !tutorial.allows_card(card.data.card_type)) =>
+        {
+            state.change_balance(drag.player, -card.data.price);
+            place(&mut ew_place_card_on_table, drag.card, marker);
+        }
+        (Some(from), Some(marker)) if free_play => {
+            if let Some(&(other, _)) = occupied.iter().find(|&&(_, other)| other == marker) {
+                place(&mut ew_place_card_on_table, other, from);
+            }
+            place(&mut ew_place_card_on_table, drag.card, marker);
+        }
+        (Some(_), None) if over_hand && free_play => {
+            state.change_balance(drag.player, card.data.price);
+            commands
+                .entity(drag.card)
+                .remove::<CardOnTable>()
+                .insert(Hand {
+                    player: drag.player,
+                });
+            card_drag.realign_hand = Some(drag.player);
+        }
+        // Dropped nowhere useful: the card goes back where it came from.
+        (Some(from), _) => place(&mut ew_place_card_on_table, drag.card, from),
+        (None, _) => {
+            ew_align_cards_in_hand.send(AlignCardsInHand {
+                player: drag.player,
+            });
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Translations of the UI and card text.
+//!
+//! Every language is a key/value file under `assets/locales`, e.g.
+//! `"ui-bank": "Bank: {amount}"`. Values name their arguments in braces.
+//! Keys missing from a language fall back to English, then to the key itself,
+//! so an unfinished translation still shows something.
+//!
+//! The files are compiled in so that text is there from the first frame,
+//! before any asset has loaded.
+
+use std::{collections::HashMap, fmt::Debug};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::cards::{CardType, TurnPhase};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Locale::new(Language::load()));
+    app.add_systems(Update, update_localized_text);
+}
+
+/// Where the chosen language is kept between sessions.
+#[cfg(not(target_family = "wasm"))]
+const LANGUAGE_PATH: &str = "language.ron";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Language {
+    pub fn next(self) -> Self {
+        match self {
+            Language::English => Language::Spanish,
+            Language::Spanish => Language::English,
+        }
+    }
+
+    /// The language's name in the language itself.
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Spanish => "Español",
+        }
+    }
+
+    fn strings(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../../assets/locales/en.ron"),
+            Language::Spanish => include_str!("../../assets/locales/es.ron"),
+        }
+    }
+
+    /// Thousands separator and whether the currency sign goes after the
+    /// amount.
+    fn number_format(self) -> (char, bool) {
+        match self {
+            Language::English => (',', false),
+            Language::Spanish => ('.', true),
+        }
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        std::fs::read_to_string(LANGUAGE_PATH)
+            .ok()
+            .and_then(|language| ron::from_str(&language).ok())
+            .unwrap_or_default()
+    }
+
+    /// The language isn't persisted on the web build.
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn save(self) {
+        let result = ron::to_string(&self)
+            .map_err(|error| error.to_string())
+            .and_then(|language| {
+                std::fs::write(LANGUAGE_PATH, language).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("Could not save the language: {}", error);
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn save(self) {}
+}
+
+fn parse(language: Language) -> HashMap<String, String> {
+    ron::from_str(language.strings()).unwrap_or_else(|error| {
+        warn!("Locale {:?} is invalid: {}", language, error);
+        HashMap::new()
+    })
+}
+
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    strings: HashMap<String, String>,
+    fallback: HashMap<String, String>,
+}
+
+/// English, for the headless server, which has no language setting.
+impl Default for Locale {
+    fn default() -> Self {
+        Self::new(Language::default())
+    }
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            strings: parse(language),
+            fallback: parse(Language::English),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        *self = Self::new(language);
+        language.save();
+    }
+
+    /// Text for `key` in the current language.
+    pub fn get(&self, key: &str) -> String {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    /// Text for `key` with every `{name}` replaced by its argument.
+    pub fn format(&self, key: &str, args: &[(&str, String)]) -> String {
+        args.iter().fold(self.get(key), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+    }
+
+    /// Name of an enum variant, looked up as `<prefix>-<variant>` in kebab
+    /// case, e.g. `phase-apply-sales-cards`.
+    pub fn name_of(&self, prefix: &str, value: impl Debug) -> String {
+        self.get(&variant_key(prefix, value))
+    }
+
+    pub fn card_name(&self, card_type: CardType) -> String {
+        self.name_of("card", card_type)
+    }
+
+    pub fn card_rules(&self, card_type: CardType) -> String {
+        self.get(&format!("{}-rules", variant_key("card", card_type)))
+    }
+
+    /// What happens during `phase`.
+    pub fn phase_hint(&self, phase: TurnPhase) -> String {
+        self.get(&format!("{}-hint", variant_key("phase", phase)))
+    }
+
+    /// A whole number with the language's thousands separator.
+    pub fn number(&self, value: i64) -> String {
+        let (separator, _) = self.language.number_format();
+        let digits = value.unsigned_abs().to_string();
+        let mut grouped = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+        if value < 0 {
+            format!("-{}", grouped)
+        } else {
+            grouped
+        }
+    }
+
+    /// An amount of dollars, e.g. `$12,500` or `12.500 $`.
+    pub fn money(&self, amount: i32) -> String {
+        let (_, sign_after) = self.language.number_format();
+        let number = self.number(i64::from(amount).abs());
+        let minus = if amount < 0 { "-" } else { "" };
+        if sign_after {
+            format!("{}{} $", minus, number)
+        } else {
+            format!("{}${}", minus, number)
+        }
+    }
+
+    /// A change of money, e.g. `+$6,000` or `-$300`.
+    pub fn signed_money(&self, amount: i32) -> String {
+        if amount > 0 {
+            format!("+{}", self.money(amount))
+        } else {
+            self.money(amount)
+        }
+    }
+}
+
+fn variant_key(prefix: &str, value: impl Debug) -> String {
+    let mut key = prefix.to_string();
+    for c in format!("{:?}", value).chars() {
+        if c.is_uppercase() {
+            key.push('-');
+        }
+        key.push(c.to_ascii_lowercase());
+    }
+    key
+}
+
+/// Fixed text of a label or button, kept in the current language.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Localized(pub &'static str);
+
+fn update_localized_text(
+    locale: Res<Locale>,
+    localized_query: Query<(Ref<Localized>, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (localized, children) in &localized_query {
+        if !locale.is_changed() && !localized.is_added() {
+            continue;
+        }
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = locale.get(localized.0);
+            }
+        }
+    }
+}
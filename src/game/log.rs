@@ -0,0 +1,264 @@
+//! Plain-language record of what happened during a match.
+//!
+//! Besides the entries written by the rules themselves, the log follows the
+//! chip and card events, new effects and how each bank moved during every
+//! phase. Online, only the server writes the log and clients receive it.
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_la_mesa::{events::PlaceCardOnTable, Card, ChipArea, Hand};
+
+use super::{
+    cards::{
+        CardCategory, ChipType, DiscardChip, DropChip, Effect, GameOver, GameState, Kard, MoveChip,
+        TurnPhase, TRANSIT_AREA,
+    },
+    locale::Locale,
+    settings::MatchSettings,
+    spawn::level::SpawnBoard,
+};
+use crate::net::client::NetClient;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GameLog>();
+    app.observe(clear_log);
+    app.add_systems(
+        Update,
+        (
+            log_played_cards,
+            log_chips,
+            log_effects,
+            log_balances,
+            log_game_over,
+        )
+            .run_if(not(resource_exists::<NetClient>)),
+    );
+}
+
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub turn_number: usize,
+    pub text: String,
+}
+
+#[derive(Resource, Default)]
+pub struct GameLog {
+    pub entries: Vec<LogEntry>,
+    /// Banks at the start of the current phase, to report what it changed.
+    banks: Vec<i32>,
+    /// Turn, player and phase the banks were taken at.
+    banks_taken_at: Option<(usize, usize, TurnPhase)>,
+    effects: Vec<Effect>,
+}
+
+impl GameLog {
+    pub fn push(&mut self, turn_number: usize, text: impl Into<String>) {
+        let text = text.into();
+        info!("Turn {}: {}", turn_number, text);
+        self.entries.push(LogEntry { turn_number, text });
+    }
+
+    /// The whole log as text, one entry per line.
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("Turn {}: {}\n", entry.turn_number, entry.text))
+            .collect()
+    }
+
+    /// Writes the log to a new file under `logs` and returns its path.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn export(&self) -> Result<String, String> {
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs());
+        let path = format!("{}/match-{}.txt", LOGS_DIR, seconds);
+        std::fs::create_dir_all(LOGS_DIR)
+            .and_then(|_| std::fs::write(&path, self.to_text()))
+            .map_err(|error| error.to_string())?;
+        Ok(path)
+    }
+}
+
+/// Where exported logs are written.
+#[cfg(not(target_family = "wasm"))]
+const LOGS_DIR: &str = "logs";
+
+fn clear_log(_trigger: Trigger<SpawnBoard>, mut log: ResMut<GameLog>) {
+    *log = GameLog::default();
+}
+
+fn log_played_cards(
+    mut er_place_card_on_table: EventReader<PlaceCardOnTable>,
+    cards: Query<(&Card<Kard>, Has<Hand>)>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+) {
+    for event in er_place_card_on_table.read() {
+        let Ok((card, in_hand)) = cards.get(event.card_entity) else {
+            continue;
+        };
+        let name = ("name", match_settings.player(event.player).name.clone());
+        let card_name = ("card", locale.card_name(card.data.card_type));
+        // Cards moved between slots were already reported when played.
+        let text = if in_hand {
+            locale.format(
+                "log-played",
+                &[
+                    name,
+                    card_name,
+                    ("amount", locale.signed_money(-card.data.price)),
+                ],
+            )
+        } else if card.data.card_type.category() == CardCategory::Event {
+            locale.format("log-drew-event", &[name, card_name])
+        } else {
+            continue;
+        };
+        log.push(state.turn_number, text);
+    }
+}
+
+/// Chip events come one chip at a time; they are summed up per frame.
+fn log_chips(
+    mut er_drop_chip: EventReader<DropChip>,
+    mut er_move_chip: EventReader<MoveChip>,
+    mut er_discard_chip: EventReader<DiscardChip>,
+    chip_areas: Query<&ChipArea>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+) {
+    let mut produced: HashMap<(usize, ChipType), usize> = HashMap::new();
+    for event in er_drop_chip.read() {
+        *produced.entry((event.player, event.chip_type)).or_default() += 1;
+    }
+    let mut moved: HashMap<(usize, usize), usize> = HashMap::new();
+    for event in er_move_chip.read() {
+        *moved.entry((event.player, event.area)).or_default() += 1;
+    }
+    let mut discarded: HashMap<usize, usize> = HashMap::new();
+    for event in er_discard_chip.read() {
+        if let Ok(area) = chip_areas.get(event.entity) {
+            *discarded.entry(area.player).or_default() += 1;
+        }
+    }
+
+    let name = |player: usize| ("name", match_settings.player(player).name.clone());
+    let count = |count: usize| ("count", count.to_string());
+    let mut texts = vec![];
+    for ((player, chip_type), produced) in produced {
+        texts.push(locale.format(
+            "log-produced",
+            &[
+                name(player),
+                count(produced),
+                ("chip", locale.name_of("chip", chip_type)),
+            ],
+        ));
+    }
+    for ((player, area), moved) in moved {
+        let key = match area {
+            2 => "log-moved-to-sales",
+            TRANSIT_AREA => "log-shipped",
+            _ => continue,
+        };
+        texts.push(locale.format(key, &[name(player), count(moved)]));
+    }
+    for (player, discarded) in discarded {
+        texts.push(locale.format("log-chips-left", &[name(player), count(discarded)]));
+    }
+    texts.sort();
+    for text in texts {
+        log.push(state.turn_number, text);
+    }
+}
+
+fn log_effects(
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let new_effects = state
+        .effects
+        .iter()
+        .filter(|effect| !log.effects.contains(effect))
+        .cloned()
+        .collect::<Vec<_>>();
+    for effect in &new_effects {
+        let key = if effect.duration == 1 {
+            "log-effect-one-turn"
+        } else {
+            "log-effect"
+        };
+        let text = locale.format(
+            key,
+            &[
+                ("effect", locale.name_of("effect", effect.effect_type)),
+                ("name", match_settings.player(effect.player).name.clone()),
+                ("turns", effect.duration.to_string()),
+            ],
+        );
+        log.push(state.turn_number, text);
+    }
+    log.effects = state.effects.clone();
+}
+
+/// Reports how each bank moved once a phase is over.
+fn log_balances(
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+) {
+    let now = (state.turn_number, state.player, state.phase);
+    if log.banks_taken_at == Some(now) {
+        return;
+    }
+    if let Some((turn_number, _, phase)) = log.banks_taken_at {
+        let changes = log
+            .banks
+            .iter()
+            .zip(&state.bank)
+            .enumerate()
+            .filter(|(_, (before, after))| before != after)
+            .map(|(index, (before, after))| (index + 1, after.saturating_sub(*before), *after))
+            .collect::<Vec<_>>();
+        for (player, change, bank) in changes {
+            let text = locale.format(
+                "log-bank",
+                &[
+                    ("name", match_settings.player(player).name.clone()),
+                    ("change", locale.signed_money(change)),
+                    ("phase", locale.name_of("phase", phase)),
+                    ("bank", locale.money(bank)),
+                ],
+            );
+            log.push(turn_number, text);
+        }
+    }
+    log.banks = state.bank.clone();
+    log.banks_taken_at = Some(now);
+}
+
+fn log_game_over(
+    mut er_game_over: EventReader<GameOver>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+) {
+    for event in er_game_over.read() {
+        let text = locale.format(
+            "log-won",
+            &[("name", match_settings.player(event.player_won).name.clone())],
+        );
+        log.push(state.turn_number, text);
+    }
+}
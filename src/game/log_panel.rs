@@ -0,0 +1,154 @@
+//! Scrollable panel showing the [`GameLog`] during a match, newest first,
+//! and the button exporting it once the match is over.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use super::{log::GameLog, spawn::level::SpawnBoard};
+use crate::screen::Screen;
+#[cfg(not(target_family = "wasm"))]
+use crate::{
+    game::{cards::GameState, locale::Locale, spawn::ui::CardGameUIAction},
+    ui::prelude::InteractionQuery,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_log_panel).add_systems(
+        Update,
+        (spawn_log_entries, scroll_log).run_if(in_state(Screen::Playing)),
+    );
+    // The web build has no file system to export to.
+    #[cfg(not(target_family = "wasm"))]
+    app.add_systems(Update, handle_export_log.run_if(in_state(Screen::Playing)));
+}
+
+const PANEL_SIZE: Vec2 = Vec2::new(360.0, 160.0);
+/// Pixels scrolled per line of the mouse wheel.
+const LINE_HEIGHT: f32 = 20.0;
+
+#[derive(Component)]
+struct LogPanel;
+
+/// Entries of the log, moved up and down inside the panel to scroll.
+#[derive(Component, Default)]
+struct LogList {
+    offset: f32,
+}
+
+fn spawn_log_panel(_trigger: Trigger<SpawnBoard>, mut commands: Commands) {
+    commands
+        .spawn((
+            Name::new("Log Panel"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(PANEL_SIZE.x),
+                    height: Val::Px(PANEL_SIZE.y),
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    padding: UiRect::all(Val::Px(6.0)),
+                    flex_direction: FlexDirection::Column,
+                    overflow: Overflow::clip_y(),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                ..default()
+            },
+            // Tells whether the cursor is over the panel.
+            Interaction::default(),
+            StateScoped(Screen::Playing),
+            LogPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Log List"),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    ..default()
+                },
+                LogList::default(),
+            ));
+        });
+}
+
+fn spawn_log_entries(
+    mut commands: Commands,
+    log: Res<GameLog>,
+    list_query: Query<Entity, With<LogList>>,
+    spawned_query: Query<(), Added<LogList>>,
+) {
+    if !log.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+    for list in &list_query {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|children| {
+                for entry in log.entries.iter().rev() {
+                    children.spawn(TextBundle::from_section(
+                        format!("{}: {}", entry.turn_number, entry.text),
+                        TextStyle {
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    }
+}
+
+fn scroll_log(
+    mut er_mouse_wheel: EventReader<MouseWheel>,
+    panel_query: Query<(&Interaction, &Node), With<LogPanel>>,
+    mut list_query: Query<(&mut LogList, &mut Style, &Node, &Parent)>,
+) {
+    let scrolled = er_mouse_wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum::<f32>();
+    if scrolled == 0.0 {
+        return;
+    }
+    for (mut list, mut style, list_node, parent) in &mut list_query {
+        let Ok((interaction, panel_node)) = panel_query.get(parent.get()) else {
+            continue;
+        };
+        if *interaction == Interaction::None {
+            continue;
+        }
+        let max_offset = (list_node.size().y - panel_node.size().y).max(0.0);
+        list.offset = (list.offset + scrolled).clamp(-max_offset, 0.0);
+        style.top = Val::Px(list.offset);
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn handle_export_log(
+    mut button_query: InteractionQuery<&CardGameUIAction>,
+    mut log: ResMut<GameLog>,
+    state: Res<GameState>,
+    locale: Res<Locale>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed)
+            || *action != CardGameUIAction::ButtonExportLog
+        {
+            continue;
+        }
+        let text = match log.export() {
+            Ok(path) => locale.format("log-exported", &[("path", path)]),
+            Err(error) => locale.format("log-export-failed", &[("error", error)]),
+        };
+        log.push(state.turn_number, text);
+    }
+}
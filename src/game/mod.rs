@@ -2,20 +2,57 @@
 
 use bevy::prelude::*;
 
+pub mod ai;
 // mod animation;
 pub mod assets;
 pub mod audio;
+pub mod camera;
+pub mod campaign;
 pub mod cards;
+pub mod checksum;
+pub mod deck_builder;
+pub mod draft;
+pub mod drag;
+pub mod locale;
+pub mod log;
+pub mod log_panel;
+pub mod mods;
+pub mod popups;
+pub mod preview;
+pub mod scoreboard;
+pub mod script;
+pub mod settings;
 pub mod spawn;
+pub mod spectator;
+pub mod trade;
+pub mod tutorial;
 pub mod ui;
 
 pub(super) fn plugin(app: &mut App) {
+    rules_plugin(app);
     app.add_plugins((
         // animation::plugin,
+        ai::plugin,
         audio::plugin,
         assets::plugin,
+        campaign::plugin,
+        drag::plugin,
+        locale::plugin,
+        log_panel::plugin,
+        mods::plugin,
+        preview::plugin,
+        scoreboard::plugin,
         spawn::plugin,
-        cards::plugin,
-        ui::plugin,
+        spectator::plugin,
+        trade::plugin,
     ));
+    app.add_plugins((camera::plugin, popups::plugin, tutorial::plugin, ui::plugin));
+}
+
+/// The match rules without anything to render, shared with the headless
+/// server.
+pub(crate) fn rules_plugin(app: &mut App) {
+    // The log is written in the default language where nothing else sets one.
+    app.init_resource::<locale::Locale>();
+    app.add_plugins((settings::plugin, cards::plugin, log::plugin));
 }
@@ -0,0 +1,642 @@
+//! Player-made content loaded from the `mods` folder next to `assets`.
+//!
+//! Every mod is a folder holding a `mod.ron` manifest along with the files it
+//! points to:
+//!
+//! ```ron
+//! (
+//!     name: "Smugglers",
+//!     cards: [
+//!         (
+//!             id: "tip_off",
+//!             price: 200,
+//!             face: "faces/tip-off.png",
+//!             script: Some("scripts/tip-off.rhai"),
+//!         ),
+//!     ],
+//!     chip_models: [(Cocaine, "models/brick.obj")],
+//!     sounds: [(ButtonPress, "sfx/click.ogg")],
+//!     presets: [
+//!         (name: "Marathon", starting_bank: 5000, winning_bank: 200000, max_turns: 40, hand_size: 5),
+//!     ],
+//! )
+//! ```
+//!
+//! Mods are checked when the game starts; broken ones are listed with their
+//! errors on the mods screen and can't be enabled. When two enabled mods
+//! provide the same card, chip model, sound or preset, the one with the
+//! higher priority wins.
+
+use bevy::{prelude::*, utils::HashSet};
+use serde::Deserialize;
+
+use super::{
+    assets::{ChipModel, HandleMap, SfxKey},
+    cards::{CardType, Kard},
+    settings::{CustomRules, RulesPreset},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Mods::load());
+    app.add_systems(Update, apply_mod_assets.run_if(resource_changed::<Mods>));
+}
+
+/// Folder holding the mods, relative to the game like `assets`.
+pub const MODS_DIR: &str = "mods";
+/// Asset source reading from [`MODS_DIR`], e.g. `mods://smugglers/faces/x.png`.
+pub const MODS_SOURCE: &str = "mods";
+const MANIFEST_FILE: &str = "mod.ron";
+/// Where the enabled mods and their order are kept between sessions.
+#[cfg(not(target_family = "wasm"))]
+const SETTINGS_PATH: &str = "mods.ron";
+
+const MAX_COPIES_PER_PLAYER: usize = 4;
+const MAX_HAND_SIZE: usize = 8;
+const MAX_TURNS: usize = 100;
+
+/// Deck a mod card is shuffled into.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+pub enum ModDeck {
+    #[default]
+    Playing,
+    Event,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ModCard {
+    /// Cards with the same id in several mods replace each other.
+    pub id: String,
+    #[serde(default = "scripted")]
+    pub card_type: CardType,
+    #[serde(default)]
+    pub price: i32,
+    /// Card face image, relative to the mod folder.
+    pub face: String,
+    /// Rhai file with the card's effect, relative to the mod folder.
+    #[serde(default)]
+    pub script: Option<String>,
+    #[serde(default)]
+    pub deck: ModDeck,
+    #[serde(default = "one")]
+    pub copies_per_player: usize,
+}
+
+fn scripted() -> CardType {
+    CardType::Scripted
+}
+
+fn one() -> usize {
+    1
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ModPreset {
+    pub name: String,
+    pub starting_bank: i32,
+    pub winning_bank: i32,
+    pub max_turns: usize,
+    pub hand_size: usize,
+}
+
+impl ModPreset {
+    pub fn rules(&self) -> CustomRules {
+        CustomRules {
+            starting_bank: self.starting_bank,
+            winning_bank: self.winning_bank,
+            max_turns: self.max_turns,
+            hand_size: self.hand_size,
+        }
+    }
+}
+
+/// On-disk shape of `mod.ron`. File paths are relative to the mod folder.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ModManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub cards: Vec<ModCard>,
+    #[serde(default)]
+    pub chip_models: Vec<(ChipModel, String)>,
+    #[serde(default)]
+    pub sounds: Vec<(SfxKey, String)>,
+    #[serde(default)]
+    pub presets: Vec<ModPreset>,
+}
+
+/// A mod found in the mods folder, valid or not.
+#[derive(Clone, Debug)]
+pub struct InstalledMod {
+    /// Folder name, which identifies the mod in the settings.
+    pub folder: String,
+    pub manifest: Option<ModManifest>,
+    /// Card scripts read from disk, by card id.
+    scripts: Vec<(String, String)>,
+    pub errors: Vec<String>,
+    pub enabled: bool,
+}
+
+impl InstalledMod {
+    pub fn name(&self) -> &str {
+        self.manifest
+            .as_ref()
+            .map_or(&self.folder, |manifest| &manifest.name)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.manifest.is_some() && self.errors.is_empty()
+    }
+
+    /// Path of one of the mod's files for the asset server.
+    fn asset_path(&self, path: &str) -> String {
+        format!("{}://{}/{}", MODS_SOURCE, self.folder, path)
+    }
+
+    fn kard(&self, card: &ModCard) -> Kard {
+        Kard {
+            card_type: card.card_type,
+            price: card.price,
+            filename: self.asset_path(&card.face),
+            script: self
+                .scripts
+                .iter()
+                .find(|(id, _)| *id == card.id)
+                .map(|(_, source)| source.clone()),
+        }
+    }
+}
+
+/// Enabled mods and their order, as saved between sessions.
+#[cfg(not(target_family = "wasm"))]
+#[derive(Default, serde::Serialize, Deserialize)]
+struct ModSettings {
+    /// Folders from the highest priority to the lowest.
+    order: Vec<String>,
+    disabled: Vec<String>,
+}
+
+/// Every installed mod, from the highest priority to the lowest.
+#[derive(Resource, Default)]
+pub struct Mods {
+    pub installed: Vec<InstalledMod>,
+}
+
+impl Mods {
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        let settings: ModSettings = std::fs::read_to_string(SETTINGS_PATH)
+            .ok()
+            .and_then(|settings| ron::from_str(&settings).ok())
+            .unwrap_or_default();
+
+        let dir = bevy::asset::io::file::FileAssetReader::get_base_path().join(MODS_DIR);
+        let mut folders = std::fs::read_dir(&dir)
+            .map(|entries| {
+                entries
+                    .filter_map(Result::ok)
+                    .filter(|entry| entry.path().is_dir())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        // Known mods keep their place, new ones come last by name.
+        folders.sort_by_key(|folder| {
+            (
+                settings
+                    .order
+                    .iter()
+                    .position(|known| known == folder)
+                    .unwrap_or(usize::MAX),
+                folder.clone(),
+            )
+        });
+
+        let installed = folders
+            .into_iter()
+            .map(|folder| {
+                let mut installed = load_mod(&dir.join(&folder), folder);
+                installed.enabled =
+                    installed.is_valid() && !settings.disabled.contains(&installed.folder);
+                for error in &installed.errors {
+                    warn!("Mod {}: {}", installed.folder, error);
+                }
+                installed
+            })
+            .collect();
+
+        Self { installed }
+    }
+
+    /// The web build has no mods folder.
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    fn save(&self) {
+        let settings = ModSettings {
+            order: self
+                .installed
+                .iter()
+                .map(|installed| installed.folder.clone())
+                .collect(),
+            disabled: self
+                .installed
+                .iter()
+                .filter(|installed| !installed.enabled)
+                .map(|installed| installed.folder.clone())
+                .collect(),
+        };
+        let result = ron::to_string(&settings)
+            .map_err(|error| error.to_string())
+            .and_then(|settings| {
+                std::fs::write(SETTINGS_PATH, settings).map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("Could not save mod settings: {}", error);
+        }
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn save(&self) {}
+
+    /// Switches a mod on or off; broken mods stay off.
+    pub fn toggle(&mut self, index: usize) {
+        let Some(installed) = self.installed.get_mut(index) else {
+            return;
+        };
+        installed.enabled = !installed.enabled && installed.is_valid();
+        self.save();
+    }
+
+    /// Gives a mod priority over the one listed before it.
+    pub fn raise(&mut self, index: usize) {
+        if index > 0 && index < self.installed.len() {
+            self.installed.swap(index - 1, index);
+            self.save();
+        }
+    }
+
+    /// Gives the mod listed after this one priority over it.
+    pub fn lower(&mut self, index: usize) {
+        self.raise(index + 1);
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &InstalledMod> {
+        self.installed.iter().filter(|installed| installed.enabled)
+    }
+
+    /// What enabled mods contribute under `key`, keeping only the one with
+    /// the highest priority for each key.
+    fn resolve<'a, T>(
+        &'a self,
+        contributions: impl Fn(&'a InstalledMod) -> Vec<(String, T)>,
+    ) -> Vec<(&'a InstalledMod, T)> {
+        let mut seen = HashSet::new();
+        let mut resolved = vec![];
+        for installed in self.enabled() {
+            for (key, item) in contributions(installed) {
+                if seen.insert(key) {
+                    resolved.push((installed, item));
+                }
+            }
+        }
+        resolved
+    }
+
+    /// Contributions of enabled mods that are hidden by a mod of higher
+    /// priority, e.g. "card tip_off: Smugglers over Cartels".
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut providers: Vec<(String, &str)> = vec![];
+        let mut conflicts = vec![];
+        for installed in self.enabled() {
+            for key in contribution_keys(installed) {
+                match providers.iter().find(|(other, _)| *other == key) {
+                    Some((_, winner)) => {
+                        conflicts.push(format!("{}: {} over {}", key, winner, installed.name()))
+                    }
+                    None => providers.push((key, installed.name())),
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Mod cards to add to a deck, with the same number of copies for every
+    /// player like the standard decks.
+    pub fn deck_cards(&self, deck: ModDeck, num_players: usize) -> Vec<Kard> {
+        self.resolve(|installed| {
+            installed
+                .manifest
+                .iter()
+                .flat_map(|manifest| &manifest.cards)
+                .filter(|card| card.deck == deck)
+                .map(|card| (format!("card {}", card.id), card))
+                .collect()
+        })
+        .into_iter()
+        .flat_map(|(installed, card)| {
+            std::iter::repeat(installed.kard(card)).take(card.copies_per_player * num_players)
+        })
+        .collect()
+    }
+
+    /// Asset paths of the chip models replaced by mods.
+    pub fn chip_models(&self) -> Vec<(ChipModel, String)> {
+        self.resolve(|installed| {
+            installed
+                .manifest
+                .iter()
+                .flat_map(|manifest| &manifest.chip_models)
+                .map(|(model, path)| (format!("chip model {:?}", model), (*model, path)))
+                .collect()
+        })
+        .into_iter()
+        .map(|(installed, (model, path))| (model, installed.asset_path(path)))
+        .collect()
+    }
+
+    /// Asset paths of the sound effects replaced by mods.
+    pub fn sounds(&self) -> Vec<(SfxKey, String)> {
+        self.resolve(|installed| {
+            installed
+                .manifest
+                .iter()
+                .flat_map(|manifest| &manifest.sounds)
+                .map(|(key, path)| (format!("sound {:?}", key), (*key, path)))
+                .collect()
+        })
+        .into_iter()
+        .map(|(installed, (key, path))| (key, installed.asset_path(path)))
+        .collect()
+    }
+
+    pub fn presets(&self) -> Vec<&ModPreset> {
+        self.resolve(|installed| {
+            installed
+                .manifest
+                .iter()
+                .flat_map(|manifest| &manifest.presets)
+                .map(|preset| (format!("preset {}", preset.name), preset))
+                .collect()
+        })
+        .into_iter()
+        .map(|(_, preset)| preset)
+        .collect()
+    }
+
+    /// Cycles through the built-in presets, then those of enabled mods.
+    pub fn next_preset(&self, preset: RulesPreset) -> RulesPreset {
+        let presets = self.presets();
+        match preset {
+            RulesPreset::HighStakes => presets.first().map_or(RulesPreset::Standard, |preset| {
+                RulesPreset::Custom(preset.rules())
+            }),
+            RulesPreset::Custom(rules) => presets
+                .iter()
+                .skip_while(|preset| preset.rules() != rules)
+                .nth(1)
+                .map_or(RulesPreset::Standard, |preset| {
+                    RulesPreset::Custom(preset.rules())
+                }),
+            preset => preset.next(),
+        }
+    }
+
+    pub fn preset_name(&self, preset: RulesPreset) -> String {
+        match preset {
+            RulesPreset::Custom(rules) => self
+                .presets()
+                .iter()
+                .find(|preset| preset.rules() == rules)
+                .map_or("Custom".to_string(), |preset| preset.name.clone()),
+            preset => format!("{:?}", preset),
+        }
+    }
+}
+
+/// Every key a mod contributes under, used to spot overrides.
+fn contribution_keys(installed: &InstalledMod) -> Vec<String> {
+    let Some(manifest) = &installed.manifest else {
+        return vec![];
+    };
+    manifest
+        .cards
+        .iter()
+        .map(|card| format!("card {}", card.id))
+        .chain(
+            manifest
+                .chip_models
+                .iter()
+                .map(|(model, _)| format!("chip model {:?}", model)),
+        )
+        .chain(
+            manifest
+                .sounds
+                .iter()
+                .map(|(key, _)| format!("sound {:?}", key)),
+        )
+        .chain(
+            manifest
+                .presets
+                .iter()
+                .map(|preset| format!("preset {}", preset.name)),
+        )
+        .collect()
+}
+
+/// Reads and checks the mod in `dir`, collecting every problem found instead
+/// of stopping at the first one.
+#[cfg(not(target_family = "wasm"))]
+fn load_mod(dir: &std::path::Path, folder: String) -> InstalledMod {
+    let mut installed = InstalledMod {
+        folder,
+        manifest: None,
+        scripts: vec![],
+        errors: vec![],
+        enabled: false,
+    };
+
+    let manifest = match std::fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            installed
+                .errors
+                .push(format!("Can't read {}: {}", MANIFEST_FILE, error));
+            return installed;
+        }
+    };
+    let manifest: ModManifest = match ron::from_str(&manifest) {
+        Ok(manifest) => manifest,
+        Err(error) => {
+            installed
+                .errors
+                .push(format!("{} is invalid: {}", MANIFEST_FILE, error));
+            return installed;
+        }
+    };
+
+    let errors = &mut installed.errors;
+    if manifest.name.trim().is_empty() {
+        errors.push("The mod has no name".to_string());
+    }
+
+    let mut ids = HashSet::new();
+    for card in &manifest.cards {
+        let what = format!("Card \"{}\"", card.id);
+        if card.id.trim().is_empty() {
+            errors.push("A card has no id".to_string());
+        } else if !ids.insert(&card.id) {
+            errors.push(format!("{} is defined twice", what));
+        }
+        if card.price < 0 {
+            errors.push(format!("{}: the price can't be negative", what));
+        }
+        if !(1..=MAX_COPIES_PER_PLAYER).contains(&card.copies_per_player) {
+            errors.push(format!(
+                "{}: copies per player must be between 1 and {}",
+                what, MAX_COPIES_PER_PLAYER
+            ));
+        }
+        check_file(
+            errors,
+            dir,
+            format!("{} face", what),
+            &card.face,
+            &["png", "jpg", "jpeg"],
+        );
+        match &card.script {
+            Some(path) => check_file(errors, dir, format!("{} script", what), path, &["rhai"]),
+            None if card.card_type == CardType::Scripted => {
+                errors.push(format!("{} is scripted but has no script", what));
+            }
+            None => {}
+        }
+    }
+
+    for (model, path) in &manifest.chip_models {
+        check_file(
+            errors,
+            dir,
+            format!("Chip model {:?}", model),
+            path,
+            &["obj"],
+        );
+    }
+    for (key, path) in &manifest.sounds {
+        check_file(errors, dir, format!("Sound {:?}", key), path, &["ogg"]);
+    }
+
+    for preset in &manifest.presets {
+        let what = format!("Preset \"{}\"", preset.name);
+        if preset.name.trim().is_empty() {
+            errors.push("A preset has no name".to_string());
+        }
+        if preset.starting_bank <= 0 {
+            errors.push(format!("{}: the starting bank must be positive", what));
+        }
+        if preset.winning_bank <= preset.starting_bank {
+            errors.push(format!(
+                "{}: the winning bank must be above the starting bank",
+                what
+            ));
+        }
+        if !(1..=MAX_TURNS).contains(&preset.max_turns) {
+            errors.push(format!(
+                "{}: max turns must be between 1 and {}",
+                what, MAX_TURNS
+            ));
+        }
+        if !(1..=MAX_HAND_SIZE).contains(&preset.hand_size) {
+            errors.push(format!(
+                "{}: the hand size must be between 1 and {}",
+                what, MAX_HAND_SIZE
+            ));
+        }
+    }
+
+    // Scripts are only compiled once their files are known to be there.
+    if installed.errors.is_empty() {
+        for card in &manifest.cards {
+            let Some(path) = &card.script else {
+                continue;
+            };
+            let result = std::fs::read_to_string(dir.join(path))
+                .map_err(|error| error.to_string())
+                .and_then(|source| super::script::check(&source).map(|_| source));
+            match result {
+                Ok(source) => installed.scripts.push((card.id.clone(), source)),
+                Err(error) => installed.errors.push(format!(
+                    "Card \"{}\" script \"{}\": {}",
+                    card.id, path, error
+                )),
+            }
+        }
+    }
+
+    installed.manifest = Some(manifest);
+    installed
+}
+
+/// Checks that a file named by the manifest exists inside the mod folder.
+#[cfg(not(target_family = "wasm"))]
+fn check_file(
+    errors: &mut Vec<String>,
+    dir: &std::path::Path,
+    what: String,
+    path: &str,
+    extensions: &[&str],
+) {
+    if let Err(error) = file_error(dir, path, extensions) {
+        errors.push(format!("{}: {}", what, error));
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn file_error(dir: &std::path::Path, path: &str, extensions: &[&str]) -> Result<(), String> {
+    let relative = std::path::Path::new(path);
+    if relative.is_absolute()
+        || relative
+            .components()
+            .any(|component| matches!(component, std::path::Component::ParentDir))
+    {
+        return Err(format!("\"{}\" is outside of the mod folder", path));
+    }
+    let has_extension = extensions.iter().any(|extension| {
+        relative
+            .extension()
+            .is_some_and(|other| other.eq_ignore_ascii_case(extension))
+    });
+    if !has_extension {
+        return Err(format!(
+            "\"{}\" isn't a {} file",
+            path,
+            extensions.join(" or ")
+        ));
+    }
+    if !dir.join(relative).is_file() {
+        return Err(format!("\"{}\" not found", path));
+    }
+    Ok(())
+}
+
+/// Swaps the chip models and sounds of enabled mods in, and back out once
+/// they are disabled.
+fn apply_mod_assets(
+    mods: Res<Mods>,
+    asset_server: Res<AssetServer>,
+    mut chip_model_handles: ResMut<HandleMap<ChipModel>>,
+    mut sfx_handles: ResMut<HandleMap<SfxKey>>,
+) {
+    *chip_model_handles = HandleMap::<ChipModel>::with_defaults(&asset_server);
+    for (model, path) in mods.chip_models() {
+        chip_model_handles.insert(model, asset_server.load(path));
+    }
+
+    *sfx_handles = HandleMap::<SfxKey>::with_defaults(&asset_server);
+    for (key, path) in mods.sounds() {
+        sfx_handles.insert(key, asset_server.load(path));
+    }
+}
@@ -0,0 +1,289 @@
+//! Feedback floating over the table: popups showing money won or spent at a
+//! seat, and a counter above every chip stack.
+//!
+//! Both are UI text following a point of the table, so they stay readable
+//! whatever the camera does.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{Chip, ChipArea};
+
+use super::{
+    cards::{ChipType, DiscardChip, DropChip, GameState, MoveChip, TRANSIT_AREA},
+    locale::Locale,
+    settings::MatchSettings,
+    spawn::level::{Seat, SpawnBoard},
+};
+use crate::{screen::Screen, GameCamera};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ShownBanks>();
+    app.observe(reset_shown_banks).observe(spawn_chip_counters);
+    app.add_systems(
+        Update,
+        (
+            spawn_money_popups,
+            animate_money_popups,
+            count_chips,
+            animate_chip_counters,
+        )
+            .chain()
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// How long a money popup stays on screen, in seconds.
+const POPUP_DURATION: f32 = 1.5;
+/// Height the popup rises by while it fades.
+const POPUP_RISE: f32 = 1.5;
+const GAIN_COLOR: Color = Color::srgb(0.3, 1.0, 0.4);
+const LOSS_COLOR: Color = Color::srgb(1.0, 0.35, 0.3);
+/// How long a counter grows when its count changes, in seconds.
+const PULSE_DURATION: f32 = 0.4;
+const PULSE_SCALE: f32 = 0.6;
+/// Height of a chip in a stack, as laid out by `handle_drop_chip`.
+const CHIP_HEIGHT: f32 = 0.2;
+
+/// Banks the popups were last shown for.
+#[derive(Resource, Default)]
+struct ShownBanks(Vec<i32>);
+
+#[derive(Component)]
+struct MoneyPopup {
+    anchor: Vec3,
+    color: Color,
+    timer: Timer,
+}
+
+/// Number of chips of one type in one area of a seat.
+#[derive(Component)]
+struct ChipCounter {
+    player: usize,
+    area: usize,
+    chip_type: ChipType,
+    count: usize,
+    pulse: Timer,
+}
+
+impl ChipCounter {
+    fn anchor(&self) -> Vec3 {
+        Seat(self.player).chip_stack_translation(self.area, self.chip_type)
+            + Vec3::Y * (self.count as f32 * CHIP_HEIGHT + 0.6)
+    }
+}
+
+/// Places `node` centered on the screen position of `point`, hiding it when
+/// the point is out of view.
+fn follow(
+    point: Vec3,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    node: &Node,
+    style: &mut Style,
+    visibility: &mut Visibility,
+) {
+    match camera.world_to_viewport(camera_transform, point) {
+        Some(position) => {
+            style.left = Val::Px(position.x - node.size().x / 2.0);
+            style.top = Val::Px(position.y - node.size().y / 2.0);
+            *visibility = Visibility::Inherited;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+fn reset_shown_banks(_trigger: Trigger<SpawnBoard>, mut shown: ResMut<ShownBanks>) {
+    shown.0.clear();
+}
+
+fn spawn_money_popups(
+    mut commands: Commands,
+    state: Res<GameState>,
+    locale: Res<Locale>,
+    mut shown: ResMut<ShownBanks>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    // Nothing was won or spent yet in this match.
+    if shown.0.len() != state.bank.len() {
+        shown.0 = state.bank.clone();
+        return;
+    }
+
+    for (index, (before, after)) in shown.0.iter().zip(&state.bank).enumerate() {
+        let change = after.saturating_sub(*before);
+        if change == 0 {
+            continue;
+        }
+        let color = if change > 0 { GAIN_COLOR } else { LOSS_COLOR };
+        commands.spawn((
+            Name::new("Money Popup"),
+            TextBundle::from_section(
+                locale.signed_money(change),
+                TextStyle {
+                    font_size: 32.0,
+                    color,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            }),
+            // Shown once placed over the seat.
+            Visibility::Hidden,
+            MoneyPopup {
+                anchor: Seat(index + 1).offset() + Vec3::Y * 2.0,
+                color,
+                timer: Timer::from_seconds(POPUP_DURATION, TimerMode::Once),
+            },
+            StateScoped(Screen::Playing),
+        ));
+    }
+    shown.0 = state.bank.clone();
+}
+
+fn animate_money_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut popups: Query<(
+        Entity,
+        &mut MoneyPopup,
+        &mut Text,
+        &Node,
+        &mut Style,
+        &mut Visibility,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    for (entity, mut popup, mut text, node, mut style, mut visibility) in &mut popups {
+        popup.timer.tick(time.delta());
+        if popup.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = popup.timer.fraction();
+        text.sections[0].style.color = popup.color.with_alpha(1.0 - progress * progress);
+        follow(
+            popup.anchor + Vec3::Y * POPUP_RISE * progress,
+            camera,
+            camera_transform,
+            node,
+            &mut style,
+            &mut visibility,
+        );
+    }
+}
+
+fn spawn_chip_counters(
+    _trigger: Trigger<SpawnBoard>,
+    mut commands: Commands,
+    match_settings: Res<MatchSettings>,
+) {
+    for player in 1..=match_settings.num_players() {
+        for area in [1, TRANSIT_AREA, 2] {
+            for chip_type in [ChipType::Cannabis, ChipType::Cocaine] {
+                commands.spawn((
+                    Name::new("Chip Counter"),
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 20.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    )
+                    .with_style(Style {
+                        padding: UiRect::horizontal(Val::Px(4.0)),
+                        position_type: PositionType::Absolute,
+                        ..default()
+                    })
+                    .with_background_color(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                    Visibility::Hidden,
+                    ChipCounter {
+                        player,
+                        area,
+                        chip_type,
+                        count: 0,
+                        pulse: Timer::from_seconds(PULSE_DURATION, TimerMode::Once),
+                    },
+                    StateScoped(Screen::Playing),
+                ));
+            }
+        }
+    }
+}
+
+/// Recounts the stacks whenever chips are dropped, moved or discarded.
+fn count_chips(
+    mut er_drop_chip: EventReader<DropChip>,
+    mut er_move_chip: EventReader<MoveChip>,
+    mut er_discard_chip: EventReader<DiscardChip>,
+    // The events are applied through commands, so the chips themselves only
+    // change a frame later.
+    changed_chips: Query<(), (With<Chip<ChipType>>, Changed<ChipArea>)>,
+    mut removed_areas: RemovedComponents<ChipArea>,
+    chips: Query<(&Chip<ChipType>, &ChipArea)>,
+    mut counters: Query<(&mut ChipCounter, &mut Text)>,
+) {
+    let events = er_drop_chip.read().count()
+        + er_move_chip.read().count()
+        + er_discard_chip.read().count()
+        + removed_areas.read().count();
+    if events == 0 && changed_chips.is_empty() {
+        return;
+    }
+    for (mut counter, mut text) in &mut counters {
+        let count = chips
+            .iter()
+            .filter(|(chip, chip_area)| {
+                chip.data == counter.chip_type
+                    && chip_area.player == counter.player
+                    && chip_area.marker == counter.area
+            })
+            .count();
+        if count == counter.count {
+            continue;
+        }
+        counter.count = count;
+        counter.pulse.reset();
+        text.sections[0].value = count.to_string();
+    }
+}
+
+fn animate_chip_counters(
+    time: Res<Time>,
+    cameras: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut counters: Query<(
+        &mut ChipCounter,
+        &Node,
+        &mut Style,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    for (mut counter, node, mut style, mut transform, mut visibility) in &mut counters {
+        if counter.count == 0 {
+            *visibility = Visibility::Hidden;
+            continue;
+        }
+        counter.pulse.tick(time.delta());
+        // Grows and shrinks back once per change.
+        let pulse = (counter.pulse.fraction() * std::f32::consts::PI).sin();
+        transform.scale = Vec3::splat(1.0 + PULSE_SCALE * pulse);
+        follow(
+            counter.anchor(),
+            camera,
+            camera_transform,
+            node,
+            &mut style,
+            &mut visibility,
+        );
+    }
+}
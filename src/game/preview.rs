@@ -0,0 +1,205 @@
+//! Enlarged preview of the card under the cursor, with its rules text and
+//! why it can't be played right now.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{
+    events::{CardHover, CardOut},
+    Card, CardOnTable, Hand,
+};
+
+use super::{
+    cards::{GameState, Kard, TurnPhase, TABLE_SLOTS},
+    locale::Locale,
+    spawn::level::SpawnBoard,
+    spectator::Spectator,
+};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HoveredCard>();
+    app.observe(spawn_preview_ui).add_systems(
+        Update,
+        (track_hovered_card, update_preview)
+            .chain()
+            .run_if(in_state(Screen::Playing)),
+    );
+}
+
+/// Card faces are drawn at this size in the preview.
+const PREVIEW_IMAGE_SIZE: Vec2 = Vec2::new(220.0, 308.0);
+
+/// Card under the cursor, if any.
+#[derive(Resource, Default)]
+pub struct HoveredCard(pub Option<Entity>);
+
+#[derive(Component)]
+struct PreviewPanel;
+
+#[derive(Component)]
+struct PreviewImage;
+
+#[derive(Component, Clone, Copy)]
+enum PreviewText {
+    Name,
+    Price,
+    Category,
+    Rules,
+    Blocked,
+}
+
+fn spawn_preview_ui(
+    _trigger: Trigger<SpawnBoard>,
+    mut commands: Commands,
+    mut hovered: ResMut<HoveredCard>,
+) {
+    hovered.0 = None;
+
+    commands
+        .spawn((
+            Name::new("Card Preview"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(PREVIEW_IMAGE_SIZE.x + 20.0),
+                    top: Val::Percent(15.0),
+                    left: Val::Px(10.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+            PreviewPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                ImageBundle {
+                    style: Style {
+                        width: Val::Px(PREVIEW_IMAGE_SIZE.x),
+                        height: Val::Px(PREVIEW_IMAGE_SIZE.y),
+                        ..default()
+                    },
+                    ..default()
+                },
+                PreviewImage,
+            ));
+            for (text, font_size, color) in [
+                (PreviewText::Name, 28.0, Color::WHITE),
+                (PreviewText::Price, 20.0, Color::WHITE),
+                (PreviewText::Category, 20.0, Color::srgb(0.7, 0.7, 0.7)),
+                (PreviewText::Rules, 18.0, Color::WHITE),
+                (PreviewText::Blocked, 18.0, Color::srgb(1.0, 0.4, 0.4)),
+            ] {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size,
+                            color,
+                            ..default()
+                        },
+                    ),
+                    text,
+                ));
+            }
+        });
+}
+
+fn track_hovered_card(
+    mut er_hover: EventReader<CardHover>,
+    mut er_out: EventReader<CardOut>,
+    mut hovered: ResMut<HoveredCard>,
+) {
+    for event in er_out.read() {
+        if hovered.0 == Some(event.card_entity) {
+            hovered.0 = None;
+        }
+    }
+    for event in er_hover.read() {
+        hovered.0 = Some(event.card_entity);
+    }
+}
+
+/// Why the current player can't play a card from `hand_player`'s hand, as
+/// a locale key.
+fn blocked_reason(
+    kard: &Kard,
+    hand_player: usize,
+    state: &GameState,
+    slots_used: usize,
+) -> Option<&'static str> {
+    if hand_player != state.player {
+        Some("preview-not-your-turn")
+    } else if state.phase != TurnPhase::PlaceCardsOnTable {
+        Some("preview-wrong-phase")
+    } else if kard.price > state.get_balance(hand_player) {
+        Some("preview-unaffordable")
+    } else if slots_used >= TABLE_SLOTS {
+        Some("preview-no-free-slot")
+    } else {
+        None
+    }
+}
+
+fn update_preview(
+    hovered: Res<HoveredCard>,
+    state: Res<GameState>,
+    locale: Res<Locale>,
+    asset_server: Res<AssetServer>,
+    spectator: Option<Res<Spectator>>,
+    cards: Query<(&Card<Kard>, Option<&Hand>)>,
+    cards_on_table: Query<&CardOnTable>,
+    mut panel_query: Query<&mut Visibility, With<PreviewPanel>>,
+    mut image_query: Query<&mut UiImage, With<PreviewImage>>,
+    mut text_query: Query<(&PreviewText, &mut Text)>,
+) {
+    if !hovered.is_changed() && !state.is_changed() && !locale.is_changed() {
+        return;
+    }
+
+    // Spectators don't get to peek at the hands.
+    let card = hovered
+        .0
+        .and_then(|entity| cards.get(entity).ok())
+        .filter(|(_, hand)| spectator.is_none() || hand.is_none());
+
+    for mut visibility in &mut panel_query {
+        *visibility = if card.is_some() {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+    let Some((card, hand)) = card else {
+        return;
+    };
+    let kard = &card.data;
+
+    for mut image in &mut image_query {
+        image.texture = asset_server.load(kard.filename.clone());
+    }
+
+    let blocked = hand.and_then(|hand| {
+        let slots_used = cards_on_table
+            .iter()
+            .filter(|card_on_table| card_on_table.player == hand.player)
+            .count();
+        blocked_reason(kard, hand.player, &state, slots_used)
+    });
+
+    for (text, mut value) in &mut text_query {
+        value.sections[0].value = match text {
+            PreviewText::Name => locale.card_name(kard.card_type),
+            PreviewText::Price => {
+                locale.format("preview-price", &[("amount", locale.money(kard.price))])
+            }
+            PreviewText::Category => locale.name_of("category", kard.card_type.category()),
+            PreviewText::Rules => locale.card_rules(kard.card_type),
+            PreviewText::Blocked => blocked.map(|key| locale.get(key)).unwrap_or_default(),
+        };
+    }
+}
@@ -0,0 +1,173 @@
+//! Scoreboard listing every player at once: bank, chips per area, effects
+//! and cards.
+//!
+//! The rows are only rewritten when something they show changes.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{Card, CardOnTable, Chip, ChipArea, Hand};
+
+use super::{
+    cards::{ChipType, GameState, Kard, TRANSIT_AREA},
+    locale::Locale,
+    settings::MatchSettings,
+    spawn::level::SpawnBoard,
+};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_scoreboard)
+        .add_systems(Update, update_scoreboard.run_if(in_state(Screen::Playing)));
+}
+
+/// Chip areas shown on the scoreboard, with their locale keys.
+const AREAS: [(usize, &str); 3] = [
+    (1, "score-production"),
+    (TRANSIT_AREA, "score-transit"),
+    (2, "score-sales"),
+];
+
+/// Text listing everything about one player but their name.
+#[derive(Component)]
+struct ScoreDetails(usize);
+
+fn spawn_scoreboard(
+    _trigger: Trigger<SpawnBoard>,
+    mut commands: Commands,
+    match_settings: Res<MatchSettings>,
+) {
+    commands
+        .spawn((
+            Name::new("Scoreboard"),
+            NodeBundle {
+                style: Style {
+                    top: Val::Px(10.0),
+                    // Clear of the side panel.
+                    right: Val::Px(226.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+        ))
+        .with_children(|parent| {
+            for player in 1..=match_settings.num_players() {
+                parent.spawn(TextBundle::from_section(
+                    match_settings.player(player).name.clone(),
+                    TextStyle {
+                        font_size: 20.0,
+                        color: match_settings.player(player).color,
+                        ..default()
+                    },
+                ));
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ),
+                    ScoreDetails(player),
+                ));
+            }
+        });
+}
+
+fn update_scoreboard(
+    state: Res<GameState>,
+    locale: Res<Locale>,
+    match_settings: Res<MatchSettings>,
+    chips: Query<(&Chip<ChipType>, &ChipArea)>,
+    hands: Query<&Hand, With<Card<Kard>>>,
+    cards_on_table: Query<&CardOnTable, With<Card<Kard>>>,
+    changed_chips: Query<(), (With<Chip<ChipType>>, Changed<ChipArea>)>,
+    changed_cards: Query<(), (With<Card<Kard>>, Or<(Changed<Hand>, Changed<CardOnTable>)>)>,
+    mut removed_areas: RemovedComponents<ChipArea>,
+    mut removed_hands: RemovedComponents<Hand>,
+    mut removed_from_table: RemovedComponents<CardOnTable>,
+    added_texts: Query<(), Added<ScoreDetails>>,
+    mut text_query: Query<(&ScoreDetails, &mut Text)>,
+) {
+    let removed = removed_areas.read().count()
+        + removed_hands.read().count()
+        + removed_from_table.read().count();
+    if !state.is_changed()
+        && !locale.is_changed()
+        && changed_chips.is_empty()
+        && changed_cards.is_empty()
+        && removed == 0
+        && added_texts.is_empty()
+    {
+        return;
+    }
+
+    for (&ScoreDetails(player), mut text) in &mut text_query {
+        if player > match_settings.num_players() {
+            continue;
+        }
+
+        let count = |area: usize, chip_type: ChipType| {
+            chips
+                .iter()
+                .filter(|(chip, chip_area)| {
+                    chip.data == chip_type && chip_area.player == player && chip_area.marker == area
+                })
+                .count()
+                .to_string()
+        };
+        let mut lines = vec![locale.format(
+            "ui-bank",
+            &[("amount", locale.money(state.get_balance(player)))],
+        )];
+        for (area, key) in AREAS {
+            lines.push(locale.format(
+                key,
+                &[
+                    ("cocaine", count(area, ChipType::Cocaine)),
+                    ("cannabis", count(area, ChipType::Cannabis)),
+                ],
+            ));
+        }
+
+        let effects = state
+            .get_effects(player)
+            .iter()
+            .map(|effect| {
+                locale.format(
+                    "score-effect",
+                    &[
+                        ("effect", locale.name_of("effect", effect.effect_type)),
+                        ("turns", effect.turns_left(&state).to_string()),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>();
+        let effects = if effects.is_empty() {
+            locale.get("ui-effects-none")
+        } else {
+            effects.join(", ")
+        };
+        lines.push(locale.format("ui-effects", &[("effects", effects)]));
+
+        let in_hand = hands.iter().filter(|hand| hand.player == player).count();
+        let in_play = cards_on_table
+            .iter()
+            .filter(|card_on_table| card_on_table.player == player)
+            .count();
+        lines.push(locale.format(
+            "score-cards",
+            &[
+                ("hand", in_hand.to_string()),
+                ("table", in_play.to_string()),
+            ],
+        ));
+
+        text.sections[0].value = lines.join("\n");
+    }
+}
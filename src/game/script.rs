@@ -0,0 +1,401 @@
+//! Card effects written in [Rhai](https://rhai.rs) instead of Rust.
+//!
+//! A card carrying a script defines one function per phase it acts in:
+//! `event`, `production`, `transport`, `sales` or `action`. Inside, `this` is
+//! the table, which can be read and asked for changes:
+//!
+//! ```rhai
+//! // A smuggler's tip: two free cocaine chips and a little heat.
+//! fn production() {
+//!     this.drop_chips(this.player, "cocaine", 2);
+//!     this.add_heat(this.player, 5);
+//! }
+//! ```
+//!
+//! Scripts never touch the world directly. They queue [`ScriptAction`]s
+//! which are checked and applied by the rules like any other card effect.
+
+use std::sync::OnceLock;
+
+use bevy::{prelude::*, utils::HashSet};
+use bevy_la_mesa::{Chip, ChipArea};
+use rhai::{
+    module_resolvers::DummyModuleResolver, CallFnOptions, Dynamic, Engine, EvalAltResult, Scope,
+};
+
+use super::{
+    cards::{ChipType, DropChip, EffectType, GameState, MoveChip, TurnPhase},
+    log::GameLog,
+};
+
+/// Upper bounds keeping a broken or hostile script from stalling the game.
+const MAX_OPERATIONS: u64 = 50_000;
+const MAX_CHIPS_PER_ACTION: i64 = 20;
+const MAX_BALANCE_PER_ACTION: i64 = 100_000;
+const MAX_EFFECT_TURNS: i64 = 5;
+
+/// Marks a card whose script already ran during a phase, since the rules
+/// run every frame until the phase advances.
+#[derive(Component)]
+pub struct ScriptRun {
+    pub turn_number: usize,
+    pub phase: TurnPhase,
+}
+
+/// Name of the script function run during a phase.
+pub fn phase_hook(phase: TurnPhase) -> Option<&'static str> {
+    match phase {
+        TurnPhase::ApplyEventCard => Some("event"),
+        TurnPhase::ApplyProductionCards => Some("production"),
+        TurnPhase::ApplyTransportationCards => Some("transport"),
+        TurnPhase::ApplySalesCards => Some("sales"),
+        TurnPhase::ApplyActionCards => Some("action"),
+        _ => None,
+    }
+}
+
+/// A change requested by a script.
+#[derive(Clone, Debug)]
+pub enum ScriptAction {
+    DropChips {
+        player: usize,
+        chip_type: ChipType,
+        count: usize,
+    },
+    MoveChips {
+        player: usize,
+        chip_type: ChipType,
+        from: usize,
+        to: usize,
+        count: usize,
+    },
+    ChangeBalance {
+        player: usize,
+        amount: i32,
+    },
+    AddHeat {
+        player: usize,
+        amount: i32,
+    },
+    AddEffect {
+        player: usize,
+        effect_type: EffectType,
+        turns: usize,
+    },
+    Log(String),
+}
+
+/// What a script sees of the match, bound to `this`.
+#[derive(Clone)]
+struct Table {
+    player: i64,
+    turn: i64,
+    num_players: i64,
+    bank: Vec<i32>,
+    heat: Vec<i32>,
+    effects: Vec<(usize, EffectType)>,
+    /// Chips per player, area and type.
+    chips: Vec<(usize, usize, ChipType)>,
+    actions: Vec<ScriptAction>,
+}
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+impl Table {
+    fn seat(&self, player: i64) -> ScriptResult<usize> {
+        if (1..=self.num_players).contains(&player) {
+            Ok(player as usize)
+        } else {
+            Err(format!("No player {}", player).into())
+        }
+    }
+
+    fn next_player(&mut self, player: i64) -> ScriptResult<i64> {
+        let seat = self.seat(player)? as i64;
+        Ok(seat % self.num_players + 1)
+    }
+
+    fn balance(&mut self, player: i64) -> ScriptResult<i64> {
+        Ok(self.bank[self.seat(player)? - 1] as i64)
+    }
+
+    fn heat(&mut self, player: i64) -> ScriptResult<i64> {
+        Ok(self.heat[self.seat(player)? - 1] as i64)
+    }
+
+    fn has_effect(&mut self, player: i64, effect: &str) -> ScriptResult<bool> {
+        let seat = self.seat(player)?;
+        let effect_type = parse_effect(effect)?;
+        Ok(self
+            .effects
+            .iter()
+            .any(|&(player, other)| player == seat && other == effect_type))
+    }
+
+    fn count_chips(&mut self, player: i64, area: i64, chip_type: &str) -> ScriptResult<i64> {
+        let seat = self.seat(player)?;
+        let area = parse_area(area)?;
+        let chip_type = parse_chip_type(chip_type)?;
+        Ok(self
+            .chips
+            .iter()
+            .filter(|chip| **chip == (seat, area, chip_type))
+            .count() as i64)
+    }
+
+    fn drop_chips(&mut self, player: i64, chip_type: &str, count: i64) -> ScriptResult<()> {
+        let action = ScriptAction::DropChips {
+            player: self.seat(player)?,
+            chip_type: parse_chip_type(chip_type)?,
+            count: count.clamp(0, MAX_CHIPS_PER_ACTION) as usize,
+        };
+        self.actions.push(action);
+        Ok(())
+    }
+
+    fn move_chips(
+        &mut self,
+        player: i64,
+        chip_type: &str,
+        from: i64,
+        to: i64,
+        count: i64,
+    ) -> ScriptResult<()> {
+        let action = ScriptAction::MoveChips {
+            player: self.seat(player)?,
+            chip_type: parse_chip_type(chip_type)?,
+            from: parse_area(from)?,
+            to: parse_area(to)?,
+            count: count.clamp(0, MAX_CHIPS_PER_ACTION) as usize,
+        };
+        self.actions.push(action);
+        Ok(())
+    }
+
+    fn change_balance(&mut self, player: i64, amount: i64) -> ScriptResult<()> {
+        let action = ScriptAction::ChangeBalance {
+            player: self.seat(player)?,
+            amount: amount.clamp(-MAX_BALANCE_PER_ACTION, MAX_BALANCE_PER_ACTION) as i32,
+        };
+        self.actions.push(action);
+        Ok(())
+    }
+
+    fn add_heat(&mut self, player: i64, amount: i64) -> ScriptResult<()> {
+        let action = ScriptAction::AddHeat {
+            player: self.seat(player)?,
+            amount: amount.clamp(-100, 100) as i32,
+        };
+        self.actions.push(action);
+        Ok(())
+    }
+
+    fn add_effect(&mut self, player: i64, effect: &str, turns: i64) -> ScriptResult<()> {
+        let action = ScriptAction::AddEffect {
+            player: self.seat(player)?,
+            effect_type: parse_effect(effect)?,
+            turns: turns.clamp(1, MAX_EFFECT_TURNS) as usize,
+        };
+        self.actions.push(action);
+        Ok(())
+    }
+
+    fn log(&mut self, text: &str) {
+        self.actions.push(ScriptAction::Log(text.to_string()));
+    }
+}
+
+fn parse_chip_type(chip_type: &str) -> ScriptResult<ChipType> {
+    match chip_type {
+        "cocaine" => Ok(ChipType::Cocaine),
+        "cannabis" => Ok(ChipType::Cannabis),
+        _ => Err(format!("Unknown chip type \"{}\"", chip_type).into()),
+    }
+}
+
+/// Production (1) or sales (2); chips on the road can't be touched.
+fn parse_area(area: i64) -> ScriptResult<usize> {
+    match area {
+        1 | 2 => Ok(area as usize),
+        _ => Err(format!("No chip area {}", area).into()),
+    }
+}
+
+fn parse_effect(effect: &str) -> ScriptResult<EffectType> {
+    match effect {
+        "drought" => Ok(EffectType::Drought),
+        "attack" => Ok(EffectType::Attack),
+        "market_crash" => Ok(EffectType::MarketCrash),
+        "border_closure" => Ok(EffectType::BorderClosure),
+        "bumper_harvest" => Ok(EffectType::BumperHarvest),
+        _ => Err(format!("Unknown effect \"{}\"", effect).into()),
+    }
+}
+
+fn engine() -> &'static Engine {
+    static ENGINE: OnceLock<Engine> = OnceLock::new();
+    ENGINE.get_or_init(|| {
+        let mut engine = Engine::new();
+        // Scripts can't load other files.
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(16);
+        engine.set_max_string_size(1024);
+        engine.set_max_array_size(256);
+
+        engine
+            .register_type_with_name::<Table>("Table")
+            .register_get("player", |table: &mut Table| table.player)
+            .register_get("turn", |table: &mut Table| table.turn)
+            .register_get("num_players", |table: &mut Table| table.num_players)
+            .register_fn("next_player", Table::next_player)
+            .register_fn("balance", Table::balance)
+            .register_fn("heat", Table::heat)
+            .register_fn("has_effect", Table::has_effect)
+            .register_fn("chips", Table::count_chips)
+            .register_fn("drop_chips", Table::drop_chips)
+            .register_fn("move_chips", Table::move_chips)
+            .register_fn("change_balance", Table::change_balance)
+            .register_fn("add_heat", Table::add_heat)
+            .register_fn("add_effect", Table::add_effect)
+            .register_fn("log", Table::log);
+        engine
+    })
+}
+
+/// Compiles `script` without running it, to report mistakes early.
+pub fn check(script: &str) -> Result<(), String> {
+    engine()
+        .compile(script)
+        .map(|_| ())
+        .map_err(|error| error.to_string())
+}
+
+/// Runs the function of `script` for the current phase and returns what it
+/// asked for. Scripts without such a function do nothing.
+pub fn run(
+    script: &str,
+    state: &GameState,
+    chips_on_table: &Query<(Entity, &Transform, &Chip<ChipType>, &ChipArea)>,
+) -> Result<Vec<ScriptAction>, String> {
+    let Some(hook) = phase_hook(state.phase) else {
+        return Ok(vec![]);
+    };
+
+    let engine = engine();
+    let ast = engine.compile(script).map_err(|error| error.to_string())?;
+    if !ast.iter_functions().any(|function| function.name == hook) {
+        return Ok(vec![]);
+    }
+
+    let table = Table {
+        player: state.player as i64,
+        turn: state.turn_number as i64,
+        num_players: state.num_players() as i64,
+        bank: state.bank.clone(),
+        heat: state.heat.clone(),
+        effects: state
+            .effects
+            .iter()
+            .map(|effect| (effect.player, effect.effect_type))
+            .collect(),
+        chips: chips_on_table
+            .iter()
+            .map(|(_, _, chip, area)| (area.player, area.marker, chip.data))
+            .collect(),
+        actions: vec![],
+    };
+
+    let mut this = Dynamic::from(table);
+    let options = CallFnOptions::new()
+        .eval_ast(false)
+        .bind_this_ptr(&mut this);
+    engine
+        .call_fn_with_options::<()>(options, &mut Scope::new(), &ast, hook, ())
+        .map_err(|error| error.to_string())?;
+
+    Ok(this
+        .try_cast::<Table>()
+        .map(|table| table.actions)
+        .unwrap_or_default())
+}
+
+/// Applies what a script asked for through the same events and state
+/// changes the built-in cards use.
+pub fn apply(
+    actions: Vec<ScriptAction>,
+    state: &mut GameState,
+    chips_on_table: &Query<(Entity, &Transform, &Chip<ChipType>, &ChipArea)>,
+    claimed_chips: &mut HashSet<Entity>,
+    ew_drop_chip: &mut EventWriter<DropChip>,
+    ew_move_chip: &mut EventWriter<MoveChip>,
+    log: &mut GameLog,
+) {
+    for action in actions {
+        match action {
+            ScriptAction::DropChips {
+                player,
+                chip_type,
+                count,
+            } => {
+                for _ in 0..count {
+                    ew_drop_chip.send(DropChip {
+                        chip_type,
+                        area: 1,
+                        player,
+                    });
+                }
+            }
+            ScriptAction::MoveChips {
+                player,
+                chip_type,
+                from,
+                to,
+                count,
+            } => {
+                let mut in_target = chips_on_table
+                    .iter()
+                    .filter(|(_, _, _, area)| area.player == player && area.marker == to)
+                    .count();
+                // Chips taken by another card in this phase are left alone.
+                let movable = chips_on_table
+                    .iter()
+                    .filter(|(entity, _, chip, area)| {
+                        !claimed_chips.contains(entity)
+                            && chip.data == chip_type
+                            && area.player == player
+                            && area.marker == from
+                    })
+                    .map(|(entity, _, _, _)| entity)
+                    .take(count)
+                    .collect::<Vec<_>>();
+                for entity in movable {
+                    if !state.has_room(player, to, in_target) {
+                        break;
+                    }
+                    in_target += 1;
+                    claimed_chips.insert(entity);
+                    ew_move_chip.send(MoveChip {
+                        entity,
+                        area: to,
+                        player,
+                    });
+                }
+            }
+            ScriptAction::ChangeBalance { player, amount } => {
+                state.change_balance(player, amount);
+            }
+            ScriptAction::AddHeat { player, amount } => {
+                state.add_heat(player, amount);
+            }
+            ScriptAction::AddEffect {
+                player,
+                effect_type,
+                turns,
+            } => {
+                state.add_effect(effect_type, turns, player);
+            }
+            ScriptAction::Log(text) => log.push(state.turn_number, text),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Match configuration chosen on the setup screen before a game starts.
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::deck_builder::DeckList;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 4;
+/// Spoilage age picked when spoilage is switched on at setup.
+pub const DEFAULT_SPOILAGE_TURNS: usize = 3;
+
+/// Colors a seat can cycle through on the setup screen.
+pub const PLAYER_COLORS: [Color; 6] = [
+    Color::srgb(0.0, 0.0, 0.345),
+    Color::srgb(0.0, 0.345, 0.0),
+    Color::srgb(0.6, 0.0, 0.0),
+    Color::srgb(0.8, 0.6, 0.0),
+    Color::srgb(0.5, 0.0, 0.6),
+    Color::srgb(0.0, 0.5, 0.6),
+];
+
+pub(super) fn plugin(app: &mut App) {
+    app.register_type::<MatchSettings>();
+    app.init_resource::<MatchSettings>();
+    app.insert_resource(GameRng(StdRng::seed_from_u64(0)));
+}
+
+/// Who takes the decisions for a seat.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum Controller {
+    #[default]
+    Human,
+    Ai,
+}
+
+#[derive(Clone, Debug, Reflect)]
+pub struct PlayerSettings {
+    pub name: String,
+    pub color: Color,
+    pub controller: Controller,
+    /// Deck built for this seat; everybody shares the standard deck when no
+    /// seat has one.
+    #[reflect(ignore)]
+    pub deck: Option<DeckList>,
+}
+
+impl PlayerSettings {
+    pub fn new(player: usize) -> Self {
+        Self {
+            name: format!("Player {}", player),
+            color: PLAYER_COLORS[(player - 1) % PLAYER_COLORS.len()],
+            controller: Controller::Human,
+            deck: None,
+        }
+    }
+}
+
+/// Bundled rule values the host picks from instead of tuning every number.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect)]
+pub enum RulesPreset {
+    #[default]
+    Standard,
+    Quick,
+    HighStakes,
+    /// Values brought by a mod, see [`super::mods`].
+    Custom(CustomRules),
+}
+
+/// Rule values of a preset that isn't built into the game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect)]
+pub struct CustomRules {
+    pub starting_bank: i32,
+    pub winning_bank: i32,
+    pub max_turns: usize,
+    pub hand_size: usize,
+}
+
+impl RulesPreset {
+    /// The next built-in preset; mod presets are cycled through by
+    /// [`super::mods::Mods::next_preset`].
+    pub fn next(self) -> Self {
+        match self {
+            RulesPreset::Standard => RulesPreset::Quick,
+            RulesPreset::Quick => RulesPreset::HighStakes,
+            RulesPreset::HighStakes | RulesPreset::Custom(_) => RulesPreset::Standard,
+        }
+    }
+
+    pub fn starting_bank(self) -> i32 {
+        match self {
+            RulesPreset::Standard => 5000,
+            RulesPreset::Quick => 5000,
+            RulesPreset::HighStakes => 2000,
+            RulesPreset::Custom(rules) => rules.starting_bank,
+        }
+    }
+
+    pub fn winning_bank(self) -> i32 {
+        match self {
+            RulesPreset::Standard => 50000,
+            RulesPreset::Quick => 20000,
+            RulesPreset::HighStakes => 100000,
+            RulesPreset::Custom(rules) => rules.winning_bank,
+        }
+    }
+
+    pub fn max_turns(self) -> usize {
+        match self {
+            RulesPreset::Standard => 20,
+            RulesPreset::Quick => 10,
+            RulesPreset::HighStakes => 30,
+            RulesPreset::Custom(rules) => rules.max_turns,
+        }
+    }
+
+    pub fn hand_size(self) -> usize {
+        match self {
+            RulesPreset::Standard | RulesPreset::Quick => 5,
+            RulesPreset::HighStakes => 6,
+            RulesPreset::Custom(rules) => rules.hand_size,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, Reflect)]
+#[reflect(Resource)]
+pub struct MatchSettings {
+    pub players: Vec<PlayerSettings>,
+    pub preset: RulesPreset,
+    pub seed: u64,
+    /// Chips left in the production area for this many turns go bad.
+    pub spoilage_turns: Option<usize>,
+    /// Players draft their decks at the table before the first turn.
+    pub draft: bool,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            players: (1..=MIN_PLAYERS).map(PlayerSettings::new).collect(),
+            preset: RulesPreset::default(),
+            seed: rand::random::<u32>() as u64,
+            spoilage_turns: None,
+            draft: false,
+        }
+    }
+}
+
+impl MatchSettings {
+    pub fn num_players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Settings of a seat, numbered from 1 like everywhere else in the game.
+    pub fn player(&self, player: usize) -> &PlayerSettings {
+        &self.players[player - 1]
+    }
+
+    pub fn set_num_players(&mut self, num_players: usize) {
+        let num_players = num_players.clamp(MIN_PLAYERS, MAX_PLAYERS);
+        while self.players.len() < num_players {
+            let player = self.players.len() + 1;
+            self.players.push(PlayerSettings::new(player));
+        }
+        self.players.truncate(num_players);
+    }
+
+    pub fn is_ai(&self, player: usize) -> bool {
+        self.players
+            .get(player - 1)
+            .is_some_and(|settings| settings.controller == Controller::Ai)
+    }
+}
+
+/// Random number generator seeded from [`MatchSettings::seed`], so that a
+/// match can be replayed by entering the same seed.
+#[derive(Resource, Deref, DerefMut)]
+pub struct GameRng(pub StdRng);
@@ -1,18 +1,26 @@
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_la_mesa::{
     events::RenderDeck, Chip, ChipArea, DeckArea, HandArea, LaMesaPluginSettings, PlayArea,
 };
-use bevy_tweening::{lens::TransformPositionLens, Animator, EaseFunction, Tween};
+use bevy_tweening::{lens::TransformPositionLens, Animator, EaseFunction, Tween, TweenCompleted};
+use rand::seq::SliceRandom;
 
 use crate::game::{
-    assets::{ChipModel, HandleMap},
+    assets::{ChipModel, HandleMap, SfxKey},
+    audio::sfx::PlaySfx,
+    campaign::ActiveScenario,
     cards::{
-        load_event_deck, load_playing_deck, ChipType, DiscardChip, DropChip, GameState, Kard,
-        MoveChip,
+        load_custom_deck, load_event_deck, load_playing_deck, ChipType, DiscardChip, DropChip,
+        GameState, Kard, MoveChip, TransferChip, MAX_HEAT, TABLE_SLOTS,
     },
+    locale::Locale,
+    log::GameLog,
+    mods::{ModDeck, Mods},
+    settings::{GameRng, MatchSettings},
 };
+use crate::net::client::NetClient;
 
 pub(super) fn plugin(app: &mut App) {
     app.observe(spawn_level);
@@ -22,11 +30,13 @@ pub(super) fn plugin(app: &mut App) {
         (
             handle_drop_chip,
             handle_move_chip_to_sales,
+            handle_transfer_chip,
             discard_chip,
+            despawn_discarded_chips,
             update_race_gem_positions,
+            update_heat_bars,
         ),
-    )
-    .add_systems(Startup, render_hand_area);
+    );
 }
 
 #[derive(Event, Debug)]
@@ -45,6 +55,89 @@ pub struct RacingGem {
     pub player: usize,
 }
 
+/// Column next to the racing track that grows with a player's heat.
+#[derive(Component)]
+pub struct HeatBar {
+    pub player: usize,
+}
+
+/// Height of a heat bar at [`MAX_HEAT`].
+const HEAT_BAR_HEIGHT: f32 = 3.0;
+
+/// Distance along x between the first and the second pair of seats.
+const SEAT_SPACING: f32 = 16.0;
+
+/// Where a player sits at the table. Odd players sit on the near side and even
+/// players face them; players 3 and 4 get their own section of the table
+/// further along x.
+#[derive(Clone, Copy, Debug)]
+pub struct Seat(pub usize);
+
+impl Seat {
+    pub fn offset(&self) -> Vec3 {
+        Vec3::new(((self.0 - 1) / 2) as f32 * SEAT_SPACING, 0.0, 0.0)
+    }
+
+    /// `1.0` for the near side of the table, `-1.0` for the far side.
+    pub fn side(&self) -> f32 {
+        if self.0 % 2 == 1 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+
+    pub fn facing(&self) -> Quat {
+        if self.side() > 0.0 {
+            Quat::IDENTITY
+        } else {
+            Quat::from_rotation_y(std::f32::consts::PI)
+        }
+    }
+
+    /// Point of the table the seat's camera looks at.
+    pub fn camera_focus(&self) -> Vec3 {
+        if self.side() > 0.0 {
+            self.offset()
+        } else {
+            self.offset() + Vec3::new(-3.0, 0.0, 0.0)
+        }
+    }
+
+    pub fn camera_transform(&self) -> Transform {
+        let focus = self.camera_focus();
+        Transform::from_translation(focus + Vec3::new(0.0, 12.0, 15.0 * self.side()))
+            .looking_at(focus, Vec3::Y)
+    }
+
+    /// Position of a play slot. Far side seats number their slots from the
+    /// right so that slot 1 is always on the player's left hand.
+    pub fn play_area_translation(&self, marker: usize) -> Vec3 {
+        let column = if self.side() > 0.0 {
+            marker - 1
+        } else {
+            5 - marker
+        };
+        self.offset() + Vec3::new(-7.6 + 3.05 * column as f32, 0.0, 7.0 * self.side())
+    }
+
+    /// Bottom of the chip stack of `chip_type` in the production (1), the
+    /// sales (2) or the transit area (3), the latter being the road beyond
+    /// the sales area.
+    pub fn chip_stack_translation(&self, marker: usize, chip_type: ChipType) -> Vec3 {
+        let base = match chip_type {
+            ChipType::Cannabis => Vec3::new(2.6, 0.1, 1.5),
+            ChipType::Cocaine => Vec3::new(3.8, 0.1, 3.3),
+        };
+        self.offset()
+            + Vec3::new(
+                base.x + 3.3 * (marker - 1) as f32,
+                base.y,
+                base.z * self.side(),
+            )
+    }
+}
+
 fn spawn_board(
     _trigger: Trigger<SpawnBoard>,
     mut commands: Commands,
@@ -53,6 +146,12 @@ fn spawn_board(
     mut meshes: ResMut<Assets<Mesh>>,
     asset_server: Res<AssetServer>,
     plugin_settings: Res<LaMesaPluginSettings>,
+    match_settings: Res<MatchSettings>,
+    game_state: Res<GameState>,
+    scenario: Option<Res<ActiveScenario>>,
+    net: Option<Res<NetClient>>,
+    mods: Res<Mods>,
+    mut rng: ResMut<GameRng>,
 ) {
     commands.spawn(PointLightBundle {
         point_light: PointLight {
@@ -72,6 +171,26 @@ fn spawn_board(
         ..default()
     });
 
+    if plugin_settings.num_players > 2 {
+        commands.spawn(PointLightBundle {
+            point_light: PointLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            transform: Transform::from_xyz(SEAT_SPACING, 7.0, 7.0),
+            ..default()
+        });
+
+        commands.spawn(PointLightBundle {
+            point_light: PointLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            transform: Transform::from_xyz(SEAT_SPACING, 7.0, -7.0),
+            ..default()
+        });
+    }
+
     // Racign Area
     commands.spawn((
         PbrBundle {
@@ -85,44 +204,56 @@ fn spawn_board(
         Name::new("Racing Area"),
     ));
 
-    // Racing Gem
+    // Racing Gems
     let sphere = meshes.add(Sphere::new(1.0).mesh().uv(120, 64));
-    commands.spawn((
-        PbrBundle {
-            mesh: sphere.clone(),
-            material: materials.add(Color::srgb_u8(0, 0, 88)),
-            transform: Transform::from_translation(Vec3::new(-7.6, 0.0, 0.0))
-                .with_scale(Vec3::ONE * 0.3),
-            ..default()
-        },
-        RacingGem { player: 1 },
-        Name::new("Racing Gem 1"),
-    ));
+    for player in 1..=plugin_settings.num_players {
+        commands.spawn((
+            PbrBundle {
+                mesh: sphere.clone(),
+                material: materials.add(match_settings.player(player).color),
+                transform: Transform::from_translation(Vec3::new(-7.6, 0.0, 0.0))
+                    .with_scale(Vec3::ONE * 0.3),
+                ..default()
+            },
+            RacingGem { player },
+            Name::new(format!("Racing Gem {}", player)),
+        ));
+    }
 
-    commands.spawn((
-        PbrBundle {
-            mesh: sphere.clone(),
-            material: materials.add(Color::srgb_u8(0, 88, 0)),
-            transform: Transform::from_translation(Vec3::new(-7.6, 0.0, 0.0))
-                .with_scale(Vec3::ONE * 0.3),
-            ..default()
-        },
-        RacingGem { player: 2 },
-        Name::new("Racing Gem 2"),
-    ));
+    // Heat Bars
+    let heat_bar = meshes.add(Cuboid::new(0.4, 1.0, 0.4));
+    for player in 1..=plugin_settings.num_players {
+        commands.spawn((
+            PbrBundle {
+                mesh: heat_bar.clone(),
+                material: materials.add(match_settings.player(player).color),
+                transform: Transform::from_translation(Vec3::new(
+                    -13.4,
+                    0.0,
+                    -1.5 + 0.6 * (player - 1) as f32,
+                ))
+                .with_scale(Vec3::new(1.0, 0.0, 1.0)),
+                ..default()
+            },
+            HeatBar { player },
+            Name::new(format!("Heat Bar {}", player)),
+        ));
+    }
 
     // Deck Area - Play Cards
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: materials.add(Color::BLACK),
-            transform: Transform::from_translation(Vec3::new(-7.6, 0.0, 0.0))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI / 2.0)),
-            ..default()
-        },
-        DeckArea { marker: 1 },
-        Name::new("Deck 1 -- Play Cards"),
-    ));
+    if !game_state.personal_decks {
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
+                material: materials.add(Color::BLACK),
+                transform: Transform::from_translation(Vec3::new(-7.6, 0.0, 0.0))
+                    .with_rotation(Quat::from_rotation_y(std::f32::consts::PI / 2.0)),
+                ..default()
+            },
+            DeckArea { marker: 1 },
+            Name::new("Deck 1 -- Play Cards"),
+        ));
+    }
 
     // Deck Area - Event Cards
     commands.spawn((
@@ -137,305 +268,181 @@ fn spawn_board(
         Name::new("Deck 2 - Event Cards"),
     ));
 
-    // Resources - Production
-
     let delta = 2.0;
 
-    let face_texture = asset_server.load("tarjetas/resources-sales.png");
-    let face_material = materials.add(StandardMaterial {
-        base_color_texture: Some(face_texture.clone()),
+    let production_material = materials.add(StandardMaterial {
+        base_color_texture: Some(asset_server.load("tarjetas/resources-sales.png")),
         ..Default::default()
     });
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(
-                Plane3d::default()
-                    .mesh()
-                    .size(2.5 * 1.2, 3.5 * 1.2)
-                    .subdivisions(10),
-            ),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(
-                1.2 + delta,
-                0.0,
-                3.5 * 1.2 / 2.0 + 0.1,
-            )),
-            ..default()
-        },
-        Name::new("Resources - Production - Player 1"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(
-                Plane3d::default()
-                    .mesh()
-                    .size(2.5 * 1.2, 3.5 * 1.2)
-                    .subdivisions(10),
-            ),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(
-                1.2 + delta,
-                0.0,
-                -(3.5 * 1.2 / 2.0 + 0.1),
-            ))
-            .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            ..default()
-        },
-        Name::new("Resources - Production - Player 2"),
-    ));
-
-    // Resources - Sales
-    let face_texture = asset_server.load("tarjetas/resources-production.png");
-    let face_material = materials.add(StandardMaterial {
-        base_color_texture: Some(face_texture.clone()),
+    let sales_material = materials.add(StandardMaterial {
+        base_color_texture: Some(asset_server.load("tarjetas/resources-production.png")),
         ..Default::default()
     });
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(
-                Plane3d::default()
-                    .mesh()
-                    .size(2.5 * 1.2, 3.5 * 1.2)
-                    .subdivisions(10),
-            ),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(
-                4.5 + delta,
-                0.0,
-                3.5 * 1.2 / 2.0 + 0.1,
-            )),
-            ..default()
-        },
-        Name::new("Resources - Sales - Player 1"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(
-                Plane3d::default()
-                    .mesh()
-                    .size(2.5 * 1.2, 3.5 * 1.2)
-                    .subdivisions(10),
-            ),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(
-                4.5 + delta,
-                0.0,
-                -(3.5 * 1.2 / 2.0 + 0.1),
-            ))
-            .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            ..default()
-        },
-        Name::new("Resources - Sales - Player 2"),
-    ));
-
-    // Play Area 1
-    let face_texture = asset_server.load("tarjetas/debug.png");
-    let face_material = materials.add(StandardMaterial {
-        base_color_texture: Some(face_texture.clone()),
+    let play_area_material = materials.add(StandardMaterial {
+        base_color_texture: Some(asset_server.load("tarjetas/debug.png")),
         ..Default::default()
     });
+    let resources_mesh = meshes.add(
+        Plane3d::default()
+            .mesh()
+            .size(2.5 * 1.2, 3.5 * 1.2)
+            .subdivisions(10),
+    );
+    let card_mesh = meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10));
+
+    for player in 1..=plugin_settings.num_players {
+        let seat = Seat(player);
+
+        // Resources - Production
+        commands.spawn((
+            PbrBundle {
+                mesh: resources_mesh.clone(),
+                material: production_material.clone(),
+                transform: Transform::from_translation(
+                    seat.offset()
+                        + Vec3::new(1.2 + delta, 0.0, (3.5 * 1.2 / 2.0 + 0.1) * seat.side()),
+                )
+                .with_rotation(seat.facing()),
+                ..default()
+            },
+            Name::new(format!("Resources - Production - Player {}", player)),
+        ));
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-0.6, 0.0, 2.8))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI / 2.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 6,
-            player: 1,
-        },
-        Name::new("Event Card - Player 1"),
-    ));
+        // Resources - Sales
+        commands.spawn((
+            PbrBundle {
+                mesh: resources_mesh.clone(),
+                material: sales_material.clone(),
+                transform: Transform::from_translation(
+                    seat.offset()
+                        + Vec3::new(4.5 + delta, 0.0, (3.5 * 1.2 / 2.0 + 0.1) * seat.side()),
+                )
+                .with_rotation(seat.facing()),
+                ..default()
+            },
+            Name::new(format!("Resources - Sales - Player {}", player)),
+        ));
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-0.6, 0.0, -2.8))
+        commands.spawn((
+            PbrBundle {
+                mesh: card_mesh.clone(),
+                material: play_area_material.clone(),
+                transform: Transform::from_translation(
+                    seat.offset() + Vec3::new(-0.6, 0.0, 2.8 * seat.side()),
+                )
                 .with_rotation(Quat::from_rotation_y(std::f32::consts::PI / 2.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 6,
-            player: 2,
-        },
-        Name::new("Event Card - Player 1"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6, 0.0, 7.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 1,
-            player: 1,
-        },
-        Name::new("Play Area 1 - Player 1"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05, 0.0, 7.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 2,
-            player: 1,
-        },
-        Name::new("Play Area 2 - Player 2"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05 * 2.0, 0.0, 7.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 3,
-            player: 1,
-        },
-        Name::new("Play Area 3 - Player 3"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05 * 3.0, 0.0, 7.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 4,
-            player: 1,
-        },
-        Name::new("Play Area 4 - Player 4"),
-    ));
-
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05 * 4.0, 0.0, 7.0)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 5,
-            player: 1,
-        },
-        Name::new("Play Area 5 - Player 5"),
-    ));
-
-    // ------------------------------
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            PlayArea { marker: 6, player },
+            Name::new(format!("Event Card - Player {}", player)),
+        ));
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6, 0.0, -7.0))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 5,
-            player: 2,
-        },
-        Name::new("Play Area 1 - Player 2"),
-    ));
+        if game_state.personal_decks {
+            commands.spawn((
+                PbrBundle {
+                    mesh: card_mesh.clone(),
+                    material: materials.add(match_settings.player(player).color),
+                    transform: Transform::from_translation(
+                        seat.offset() + Vec3::new(-3.6, 0.0, 5.8 * seat.side()),
+                    )
+                    .with_rotation(seat.facing()),
+                    ..default()
+                },
+                DeckArea {
+                    marker: game_state.playing_deck(player),
+                },
+                Name::new(format!("Deck - Play Cards - Player {}", player)),
+            ));
+        }
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05, 0.0, -7.0))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 4,
-            player: 2,
-        },
-        Name::new("Play Area 2 - Player 2"),
-    ));
+        for marker in 1..=TABLE_SLOTS {
+            commands.spawn((
+                PbrBundle {
+                    mesh: card_mesh.clone(),
+                    material: play_area_material.clone(),
+                    transform: Transform::from_translation(seat.play_area_translation(marker))
+                        .with_rotation(seat.facing()),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                PlayArea { marker, player },
+                Name::new(format!("Play Area {} - Player {}", marker, player)),
+            ));
+        }
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05 * 2.0, 0.0, -7.0))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 3,
-            player: 2,
-        },
-        Name::new("Play Area 3 - Player 2"),
-    ));
+        let hand_rotation = if seat.side() > 0.0 {
+            Quat::from_rotation_x(std::f32::consts::PI / 4.0)
+        } else {
+            Quat::from_rotation_x(-std::f32::consts::PI / 4.0)
+                * Quat::from_rotation_y(std::f32::consts::PI)
+        };
+        commands.spawn((
+            Name::new(format!("HandArea - Player {}", player)),
+            TransformBundle {
+                local: Transform::from_translation(
+                    seat.offset() + Vec3::new(0.0, 1.5, 5.8 * seat.side()),
+                )
+                .with_rotation(hand_rotation),
+                ..default()
+            },
+            HandArea { player },
+        ));
+    }
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05 * 3.0, 0.0, -7.0))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
-            marker: 2,
-            player: 2,
-        },
-        Name::new("Play Area 4 - Player 2"),
-    ));
+    // Online, cards are only rendered once the server reveals them.
+    if net.is_some() {
+        return;
+    }
 
-    commands.spawn((
-        PbrBundle {
-            mesh: meshes.add(Plane3d::default().mesh().size(2.5, 3.5).subdivisions(10)),
-            material: face_material.clone(),
-            transform: Transform::from_translation(Vec3::new(-7.6 + 3.05 * 4.0, 0.0, -7.0))
-                .with_rotation(Quat::from_rotation_y(std::f32::consts::PI)),
-            visibility: Visibility::Hidden,
-            ..default()
-        },
-        PlayArea {
+    let scenario = scenario.as_ref().map(|active| &active.scenario);
+
+    // Players with a deck of their own draw from it, the others from a copy
+    // of their share of the standard deck.
+    if game_state.personal_decks {
+        for player in 1..=plugin_settings.num_players {
+            let mut deck = match &match_settings.player(player).deck {
+                Some(deck) => deck.kards(),
+                None => {
+                    let mut deck = load_playing_deck(1);
+                    deck.extend(mods.deck_cards(ModDeck::Playing, 1));
+                    deck
+                }
+            };
+            deck.shuffle(&mut rng.0);
+            ew_render_deck.send(RenderDeck::<Kard> {
+                marker: game_state.playing_deck(player),
+                deck,
+            });
+        }
+    } else {
+        let mut playing_deck = match scenario.and_then(|scenario| scenario.playing_deck.as_ref()) {
+            Some(cards) => load_custom_deck(cards),
+            None => {
+                let mut deck = load_playing_deck(plugin_settings.num_players);
+                deck.extend(mods.deck_cards(ModDeck::Playing, plugin_settings.num_players));
+                deck
+            }
+        };
+        playing_deck.shuffle(&mut rng.0);
+        ew_render_deck.send(RenderDeck::<Kard> {
             marker: 1,
-            player: 2,
-        },
-        Name::new("Play Area 5 - Player 2"),
-    ));
-
-    ew_render_deck.send(RenderDeck::<Kard> {
-        marker: 1,
-        deck: load_playing_deck(plugin_settings.num_players),
-    });
+            deck: playing_deck,
+        });
+    }
 
+    let mut event_deck = match scenario.and_then(|scenario| scenario.event_deck.as_ref()) {
+        Some(cards) => load_custom_deck(cards),
+        None => {
+            let mut deck = load_event_deck(plugin_settings.num_players);
+            deck.extend(mods.deck_cards(ModDeck::Event, plugin_settings.num_players));
+            deck
+        }
+    };
+    event_deck.shuffle(&mut rng.0);
     ew_render_deck.send(RenderDeck::<Kard> {
         marker: 2,
-        deck: load_event_deck(plugin_settings.num_players),
+        deck: event_deck,
     });
 }
 
@@ -445,44 +452,51 @@ pub fn handle_drop_chip(
     query: Query<(Entity, &ChipArea, &Chip<ChipType>)>,
     game_state: Res<GameState>,
     chip_model_handles: Res<HandleMap<ChipModel>>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
 ) {
-    let mut cocaine_counter = 0;
-    let mut cannabis_counter = 0;
+    // Chips dropped this frame onto each stack, which the query can't see yet.
+    let mut n_chips_dropped: HashMap<(usize, usize, ChipType), usize> = HashMap::new();
+    let mut dropped_per_player: HashMap<usize, usize> = HashMap::new();
+    let mut lost_per_player: HashMap<usize, usize> = HashMap::new();
     for drop_chip in er_drop_chip.read() {
+        // Production beyond the warehouse capacity is lost.
+        let chips_in_area = query
+            .iter()
+            .filter(|(_, area, _)| area.player == drop_chip.player && area.marker == drop_chip.area)
+            .count();
+        let dropped = dropped_per_player.entry(drop_chip.player).or_default();
+        if !game_state.has_room(drop_chip.player, drop_chip.area, chips_in_area + *dropped) {
+            *lost_per_player.entry(drop_chip.player).or_default() += 1;
+            continue;
+        }
+        *dropped += 1;
+
+        let key = (drop_chip.player, drop_chip.area, drop_chip.chip_type);
         let num_chips_of_kind = query
             .iter()
             .filter(|(_, area, chip)| {
                 area.player == drop_chip.player
-                    && area.marker == 1
+                    && area.marker == drop_chip.area
                     && chip.data == drop_chip.chip_type
             })
             .count();
+        let n_dropped = n_chips_dropped.entry(key).or_default();
 
         let model = match drop_chip.chip_type {
             ChipType::Cannabis => chip_model_handles.get(&ChipModel::Cannabis).unwrap(),
             ChipType::Cocaine => chip_model_handles.get(&ChipModel::Cocaine).unwrap(),
         };
 
-        let delta = 2.0;
-
-        let mut initial_translation = match drop_chip.chip_type {
-            ChipType::Cannabis => {
-                Transform::from_xyz(0.6 + delta, 12.0, 1.5).with_scale(Vec3::ONE * 1.0)
-            }
-            ChipType::Cocaine => {
-                Transform::from_xyz(1.8 + delta, 12.0, 3.3).with_scale(Vec3::ONE * 1.0)
-            }
-        }
-        .translation;
-        initial_translation.z *= if drop_chip.player == 1 { 1.0 } else { -1.0 };
+        let seat = Seat(drop_chip.player);
+        let mut initial_translation =
+            seat.chip_stack_translation(drop_chip.area, drop_chip.chip_type);
+        initial_translation.y = 12.0;
 
         let mut final_translation = initial_translation;
-        final_translation.y = 0.1
-            + (match drop_chip.chip_type {
-                ChipType::Cannabis => cannabis_counter,
-                ChipType::Cocaine => cocaine_counter,
-            } + num_chips_of_kind) as f32
-                * 0.2;
+        final_translation.y = 0.1 + (*n_dropped + num_chips_of_kind) as f32 * 0.2;
+        *n_dropped += 1;
 
         let tween: Tween<Transform> = Tween::new(
             EaseFunction::QuadraticIn,
@@ -510,7 +524,12 @@ pub fn handle_drop_chip(
             Chip::<ChipType> {
                 data: drop_chip.chip_type,
                 turn_activation_1: game_state.turn_number,
-                turn_activation_2: 0,
+                // Same as a chip moved into the sales area.
+                turn_activation_2: if drop_chip.area == 2 {
+                    game_state.turn_number
+                } else {
+                    0
+                },
             },
             ChipArea {
                 player: drop_chip.player,
@@ -518,11 +537,17 @@ pub fn handle_drop_chip(
             },
             Animator::new(tween),
         ));
+    }
 
-        match drop_chip.chip_type {
-            ChipType::Cannabis => cannabis_counter += 1,
-            ChipType::Cocaine => cocaine_counter += 1,
-        }
+    for (player, lost) in lost_per_player {
+        let text = locale.format(
+            "log-warehouse-full",
+            &[
+                ("name", match_settings.player(player).name.clone()),
+                ("count", lost.to_string()),
+            ],
+        );
+        log.push(game_state.turn_number, text);
     }
 }
 
@@ -531,32 +556,32 @@ pub fn handle_move_chip_to_sales(
     mut er_move_chip: EventReader<MoveChip>,
     query: Query<(Entity, &Transform, &ChipArea, &Chip<ChipType>)>,
 ) {
-    let mut n_cocaine_chips_moved = 0;
-    let mut n_cannabis_chips_moved = 0;
-    for (_i, move_chip) in er_move_chip.read().enumerate() {
-        let chip = query.get(move_chip.entity).unwrap();
-        let chip_type = chip.3.data;
-        let initial_translation = chip.1.translation;
+    let mut n_chips_moved: HashMap<(usize, usize, ChipType), usize> = HashMap::new();
+    for move_chip in er_move_chip.read() {
+        let Ok((_, transform, _, chip)) = query.get(move_chip.entity) else {
+            continue;
+        };
+        let chip_type = chip.data;
+        let key = (move_chip.player, move_chip.area, chip_type);
         let num_chips_of_kind = query
             .iter()
-            .filter(|(_, _, area, chip)| area.marker == move_chip.area && chip.data == chip_type)
-            .count();
+            .filter(|(_, _, area, chip)| {
+                area.player == move_chip.player
+                    && area.marker == move_chip.area
+                    && chip.data == chip_type
+            })
+            .count()
+            + n_chips_moved.get(&key).copied().unwrap_or(0);
 
-        let mut final_translation = initial_translation;
-        final_translation.x += 3.3;
-        final_translation.y = 0.1
-            + (num_chips_of_kind
-                + match chip_type {
-                    ChipType::Cannabis => n_cannabis_chips_moved,
-                    ChipType::Cocaine => n_cocaine_chips_moved,
-                }) as f32
-                * 0.2;
+        let mut final_translation =
+            Seat(move_chip.player).chip_stack_translation(move_chip.area, chip_type);
+        final_translation.y += num_chips_of_kind as f32 * 0.2;
 
         let tween: Tween<Transform> = Tween::new(
             EaseFunction::QuadraticIn,
             Duration::from_millis(350),
             TransformPositionLens {
-                start: initial_translation,
+                start: transform.translation,
                 end: final_translation,
             },
         );
@@ -569,79 +594,128 @@ pub fn handle_move_chip_to_sales(
                 player: move_chip.player,
             });
 
-        match chip_type {
-            ChipType::Cannabis => n_cannabis_chips_moved += 1,
-            ChipType::Cocaine => n_cocaine_chips_moved += 1,
-        }
+        *n_chips_moved.entry(key).or_default() += 1;
+    }
+}
+
+pub fn handle_transfer_chip(
+    mut commands: Commands,
+    mut er_transfer_chip: EventReader<TransferChip>,
+    query: Query<(Entity, &Transform, &ChipArea, &Chip<ChipType>)>,
+) {
+    let mut n_chips_transferred: HashMap<(usize, usize, ChipType), usize> = HashMap::new();
+    for transfer_chip in er_transfer_chip.read() {
+        let Ok((_, transform, area, chip)) = query.get(transfer_chip.entity) else {
+            continue;
+        };
+        let key = (transfer_chip.player, area.marker, chip.data);
+        let num_chips_of_kind = query
+            .iter()
+            .filter(|(_, _, other_area, other_chip)| {
+                other_area.player == transfer_chip.player
+                    && other_area.marker == area.marker
+                    && other_chip.data == chip.data
+            })
+            .count()
+            + n_chips_transferred.get(&key).copied().unwrap_or(0);
+
+        let mut final_translation =
+            Seat(transfer_chip.player).chip_stack_translation(area.marker, chip.data);
+        final_translation.y += num_chips_of_kind as f32 * 0.2;
+
+        let tween: Tween<Transform> = Tween::new(
+            EaseFunction::QuadraticInOut,
+            Duration::from_millis(600),
+            TransformPositionLens {
+                start: transform.translation,
+                end: final_translation,
+            },
+        );
+
+        commands
+            .entity(transfer_chip.entity)
+            .insert(Animator::new(tween))
+            .insert(ChipArea {
+                marker: area.marker,
+                player: transfer_chip.player,
+            });
+
+        *n_chips_transferred.entry(key).or_default() += 1;
     }
 }
 
+/// Where sold chips fly to: the middle of the racing track, standing for the
+/// buyers paying into the banks.
+const BUYER_TRANSLATION: Vec3 = Vec3::new(-7.6, 2.0, 0.0);
+/// Marks the tweens of discarded chips, which despawn once they end.
+const DISCARD_TWEEN: u64 = 1;
+
+/// Takes discarded chips out of their area right away, so that the rules no
+/// longer count them, and flies them off the table: to the buyers when sold
+/// from the sales area, up and away otherwise.
 pub fn discard_chip(
     mut commands: Commands,
     mut er_discard_chip: EventReader<DiscardChip>,
-    _query: Query<(Entity, &Transform, &Chip<ChipType>)>,
+    query: Query<(&Transform, &ChipArea), With<Chip<ChipType>>>,
 ) {
+    let mut discarded = vec![];
+    let mut sold = false;
     for discard_chip in er_discard_chip.read() {
-        if commands.get_entity(discard_chip.entity).is_none() {
+        if discarded.contains(&discard_chip.entity) {
             continue;
         }
-        commands.entity(discard_chip.entity).despawn_recursive();
-        // let chip = query.get(discard_chip.entity).unwrap();
-        // let initial_translation = chip.1.translation;
-
-        // let mut final_translation = initial_translation;
-        // final_translation.y = 120.0;
-
-        // let tween: Tween<Transform> = Tween::new(
-        //     EaseFunction::QuadraticIn,
-        //     Duration::from_millis(350),
-        //     TransformPositionLens {
-        //         start: initial_translation,
-        //         end: final_translation,
-        //     },
-        // );
-
-        // commands
-        //     .entity(discard_chip.entity)
-        //     .insert(Animator::new(tween))
-        //     .remove::<ChipArea>();
+        let Ok((transform, area)) = query.get(discard_chip.entity) else {
+            continue;
+        };
+        let start = transform.translation;
+        let end = if area.marker == 2 {
+            sold = true;
+            BUYER_TRANSLATION
+        } else {
+            Vec3::new(start.x, 12.0, start.z)
+        };
+
+        // Chips of the same stack leave one after the other.
+        let tween: Tween<Transform> = Tween::new(
+            EaseFunction::QuadraticIn,
+            Duration::from_millis(350 + 40 * discarded.len() as u64),
+            TransformPositionLens { start, end },
+        )
+        .with_completed_event(DISCARD_TWEEN);
+
+        commands
+            .entity(discard_chip.entity)
+            .insert(Animator::new(tween))
+            .remove::<ChipArea>();
+        discarded.push(discard_chip.entity);
+    }
+    if sold {
+        commands.trigger(PlaySfx::Key(SfxKey::ChipSale));
     }
 }
 
-pub fn render_hand_area(mut commands: Commands) {
-    commands.spawn((
-        Name::new("HandArea - Player 1"),
-        TransformBundle {
-            local: Transform::from_translation(Vec3::new(0.0, 1.5, 5.8))
-                .with_rotation(Quat::from_rotation_x(std::f32::consts::PI / 4.0)),
-            ..default()
-        },
-        HandArea { player: 1 },
-    ));
-
-    commands.spawn((
-        Name::new("HandArea - Player 2"),
-        TransformBundle {
-            local: Transform::from_translation(Vec3::new(0.0, 1.5, -5.8)).with_rotation(
-                Quat::from_rotation_x(-std::f32::consts::PI / 4.0)
-                    * Quat::from_rotation_y(std::f32::consts::PI),
-            ),
-            ..default()
-        },
-        HandArea { player: 2 },
-    ));
+pub fn despawn_discarded_chips(
+    mut commands: Commands,
+    mut er_tween_completed: EventReader<TweenCompleted>,
+) {
+    for event in er_tween_completed.read() {
+        if event.user_data != DISCARD_TWEEN {
+            continue;
+        }
+        if let Some(entity) = commands.get_entity(event.entity) {
+            entity.despawn_recursive();
+        }
+    }
 }
 
 pub fn update_race_gem_positions(
     mut query: Query<(Entity, &RacingGem, &mut Transform)>,
     game_state: Res<GameState>,
 ) {
-    let goal = 50000.0;
+    let goal = game_state.winning_bank as f32;
+    let lanes = (game_state.num_players() - 1).max(1) as f32;
     for (_, gem, mut transform) in query.iter_mut() {
-        let radius = match gem.player {
-            1 => 4.2,
-            _ => 4.8,
-        };
+        let radius = 4.2 + 0.6 * (gem.player - 1) as f32 / lanes;
 
         let current_score: i32 = game_state.get_balance(gem.player);
         let percent_of_lap = (current_score as f32) / goal;
@@ -651,3 +725,11 @@ pub fn update_race_gem_positions(
         transform.translation.z = radius * angle.sin();
     }
 }
+
+pub fn update_heat_bars(mut query: Query<(&HeatBar, &mut Transform)>, game_state: Res<GameState>) {
+    for (bar, mut transform) in query.iter_mut() {
+        let height = HEAT_BAR_HEIGHT * game_state.get_heat(bar.player) as f32 / MAX_HEAT as f32;
+        transform.scale.y = height.max(0.01);
+        transform.translation.y = height / 2.0;
+    }
+}
@@ -4,7 +4,12 @@ use bevy::prelude::*;
 use bevy_la_mesa::events::{CardPress, PlaceCardOnTable};
 use bevy_la_mesa::{Card, CardOnTable, Hand};
 
-use crate::game::cards::{GameState, Kard};
+use crate::game::campaign::ActiveScenario;
+use crate::game::cards::{GameState, Kard, LOAN_AMOUNT, TABLE_SLOTS};
+use crate::game::drag::CardDrag;
+use crate::game::locale::{Locale, Localized};
+use crate::game::tutorial::Tutorial;
+use crate::net::{client::NetClient, protocol::ClientMessage};
 use crate::screen::Screen;
 use crate::ui::widgets::Widgets;
 
@@ -19,14 +24,23 @@ pub enum CardGameUIAction {
     ButtonMoveChip,
     ButtonAdvancePhase,
     ButtonSwitchPlayer,
+    ButtonBorrow,
+    ButtonRepay,
+    ButtonCargo,
     LabelPlayerNumber,
     LabelTurnNumber,
     LabelTurnPhase,
     LabelPhaseDescription,
     LabelBank,
+    LabelDebt,
+    LabelHeat,
+    LabelMarkets,
     LabelEffects,
+    LabelInformant,
     LabelGameOver,
     ContainerGameOver,
+    ButtonExportLog,
+    ButtonBackToCampaign,
 }
 
 pub(super) fn plugin(app: &mut App) {
@@ -34,7 +48,12 @@ pub(super) fn plugin(app: &mut App) {
         .add_systems(Update, handle_card_press);
 }
 
-fn spawn_card_game_ui(_trigger: Trigger<SpawnBoard>, mut commands: Commands) {
+fn spawn_card_game_ui(
+    _trigger: Trigger<SpawnBoard>,
+    mut commands: Commands,
+    locale: Res<Locale>,
+    scenario: Option<Res<ActiveScenario>>,
+) {
     commands
         .spawn((
             Name::new("UI Root"),
@@ -60,7 +79,7 @@ fn spawn_card_game_ui(_trigger: Trigger<SpawnBoard>, mut commands: Commands) {
                 .label("Turn number: 1")
                 .insert(CardGameUIAction::LabelTurnNumber);
             children
-                .label("Player number: 1")
+                .label("Player: 1")
                 .insert(CardGameUIAction::LabelPlayerNumber);
             children
                 .label("Turn phase: Prepare")
@@ -74,18 +93,41 @@ fn spawn_card_game_ui(_trigger: Trigger<SpawnBoard>, mut commands: Commands) {
             children
                 .label("Bank: $0")
                 .insert(CardGameUIAction::LabelBank);
+            children
+                .label("Debt: $0")
+                .insert(CardGameUIAction::LabelDebt);
+            children
+                .label("Heat: 0")
+                .insert(CardGameUIAction::LabelHeat);
+            children
+                .label("Demand")
+                .insert(CardGameUIAction::LabelMarkets);
+            children
+                .label("")
+                .insert((CardGameUIAction::LabelInformant, Visibility::Hidden));
             // children
             //     .button("Switch Player")
             //     .insert(CardGameUIAction::ButtonSwitchPlayer);
+            children.button(locale.get("ui-shuffle-deck")).insert((
+                CardGameUIAction::ButtonShuffleDeck,
+                Localized("ui-shuffle-deck"),
+            ));
+            children
+                .button(locale.get("ui-draw-hand"))
+                .insert((CardGameUIAction::ButtonDrawHand, Localized("ui-draw-hand")));
             children
-                .button("Shuffle Deck")
-                .insert(CardGameUIAction::ButtonShuffleDeck);
+                .button(locale.format("ui-borrow", &[("amount", locale.money(LOAN_AMOUNT))]))
+                .insert(CardGameUIAction::ButtonBorrow);
             children
-                .button("Draw Hand")
-                .insert(CardGameUIAction::ButtonDrawHand);
+                .button(locale.format("ui-repay", &[("amount", locale.money(LOAN_AMOUNT))]))
+                .insert(CardGameUIAction::ButtonRepay);
             children
-                .button("Advance Phase")
-                .insert(CardGameUIAction::ButtonAdvancePhase);
+                .button("Load: Mixed")
+                .insert(CardGameUIAction::ButtonCargo);
+            children.button(locale.get("ui-advance-phase")).insert((
+                CardGameUIAction::ButtonAdvancePhase,
+                Localized("ui-advance-phase"),
+            ));
             // children
             //     .button("Drop Chip")
             //     .insert(CardGameUIAction::ButtonDropChip);
@@ -130,6 +172,18 @@ fn spawn_card_game_ui(_trigger: Trigger<SpawnBoard>, mut commands: Commands) {
                     ..Default::default()
                 })
                 .insert((CardGameUIAction::LabelGameOver));
+            // The web build has no file system to export to.
+            #[cfg(not(target_family = "wasm"))]
+            parent.button(locale.get("ui-export-log")).insert((
+                CardGameUIAction::ButtonExportLog,
+                Localized("ui-export-log"),
+            ));
+            if scenario.is_some() {
+                parent.button(locale.get("ui-back-to-campaign")).insert((
+                    CardGameUIAction::ButtonBackToCampaign,
+                    Localized("ui-back-to-campaign"),
+                ));
+            }
         });
 }
 
@@ -139,15 +193,38 @@ pub fn handle_card_press(
     query_cards_on_table: Query<(Entity, &Card<Kard>, &CardOnTable)>,
     mut ew_place_card_on_table: EventWriter<PlaceCardOnTable>,
     mut state: ResMut<GameState>,
+    tutorial: Option<Res<Tutorial>>,
+    net: Option<Res<NetClient>>,
+    card_drag: Res<CardDrag>,
 ) {
     let player = state.player;
     for event in card_press.read() {
+        if card_drag.holds(event.card_entity) {
+            continue;
+        }
         let hand = query_cards_in_hand.get(event.card_entity).ok();
         if hand.is_none() {
             continue;
         }
         let (_, kard, hand) = hand.unwrap();
 
+        // Online, the server checks and places the card.
+        if let Some(net) = net.as_ref() {
+            if let Some(card) = net.net_id(event.card_entity) {
+                if net.is_my_turn(&state) {
+                    net.send(ClientMessage::PlayCard { card });
+                }
+            }
+            continue;
+        }
+
+        if tutorial
+            .as_ref()
+            .is_some_and(|tutorial| !tutorial.allows_card(kard.data.card_type))
+        {
+            continue;
+        }
+
         if kard.data.price > state.get_balance(state.player) {
             continue;
         }
@@ -160,7 +237,7 @@ pub fn handle_card_press(
             .collect();
 
         let marker = markers.iter().max().unwrap_or(&0) + 1;
-        if marker > 5 {
+        if marker > TABLE_SLOTS {
             continue;
         }
 
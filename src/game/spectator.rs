@@ -0,0 +1,76 @@
+//! Watching a match without a seat: AI-only games and online spectators.
+//!
+//! Spectators move the camera freely and only see what every player can see;
+//! cards in hand stay hidden.
+
+use bevy::{input::mouse::MouseWheel, prelude::*};
+use bevy_la_mesa::{Card, Hand};
+
+use super::cards::Kard;
+use crate::{screen::Screen, GameCamera};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (move_free_camera, hide_hands)
+            .run_if(in_state(Screen::Playing).and_then(resource_exists::<Spectator>)),
+    );
+}
+
+/// Units per second the camera pans at.
+const PAN_SPEED: f32 = 12.0;
+/// Radians per second the camera turns at.
+const TURN_SPEED: f32 = 1.5;
+/// Units the camera moves per step of the mouse wheel.
+const ZOOM_STEP: f32 = 1.5;
+const MIN_CAMERA_HEIGHT: f32 = 3.0;
+const MAX_CAMERA_HEIGHT: f32 = 40.0;
+
+/// Present while the local player only watches.
+#[derive(Resource, Default)]
+pub struct Spectator;
+
+/// WASD or arrows to pan, Q and E to turn, the mouse wheel to zoom.
+fn move_free_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut er_mouse_wheel: EventReader<MouseWheel>,
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+) {
+    let pressed = |codes: &[KeyCode]| keys.any_pressed(codes.iter().copied()) as i32 as f32;
+    let forward_input =
+        pressed(&[KeyCode::KeyW, KeyCode::ArrowUp]) - pressed(&[KeyCode::KeyS, KeyCode::ArrowDown]);
+    let right_input = pressed(&[KeyCode::KeyD, KeyCode::ArrowRight])
+        - pressed(&[KeyCode::KeyA, KeyCode::ArrowLeft]);
+    let turn_input = pressed(&[KeyCode::KeyQ]) - pressed(&[KeyCode::KeyE]);
+    let zoom_input = er_mouse_wheel.read().map(|event| event.y).sum::<f32>();
+
+    for mut transform in &mut camera_query {
+        // Panning stays parallel to the table whatever the camera's pitch.
+        let forward =
+            Vec3::new(transform.forward().x, 0.0, transform.forward().z).normalize_or_zero();
+        let right = Vec3::new(transform.right().x, 0.0, transform.right().z).normalize_or_zero();
+        transform.translation +=
+            (forward * forward_input + right * right_input) * PAN_SPEED * time.delta_seconds();
+        transform.rotate_y(turn_input * TURN_SPEED * time.delta_seconds());
+
+        let zoomed = transform.translation + transform.forward() * zoom_input * ZOOM_STEP;
+        if (MIN_CAMERA_HEIGHT..=MAX_CAMERA_HEIGHT).contains(&zoomed.y) {
+            transform.translation = zoomed;
+        }
+    }
+}
+
+/// Hides cards while they are in a hand and shows them again once played.
+fn hide_hands(mut cards: Query<(&mut Visibility, Has<Hand>), With<Card<Kard>>>) {
+    for (mut visibility, in_hand) in &mut cards {
+        let wanted = if in_hand {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
+}
@@ -0,0 +1,602 @@
+//! Player-to-player trades of chips and cash during `PlaceCardsOnTable`.
+//!
+//! The active player drafts an offer on the trade panel, the target player
+//! accepts or rejects it, and accepted trades hand the chip entities over
+//! with [`TransferChip`]. Trades are only available offline.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{Chip, ChipArea};
+
+use super::{
+    cards::{ChipType, GameState, TransferChip, TurnPhase},
+    locale::{Locale, Localized},
+    log::GameLog,
+    settings::MatchSettings,
+    spawn::level::SpawnBoard,
+    tutorial::Tutorial,
+};
+use crate::{net::client::NetClient, screen::Screen, ui::prelude::*};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<TradeDesk>()
+        .add_event::<ProposeTrade>()
+        .add_event::<AnswerTrade>()
+        .observe(spawn_trade_ui)
+        .add_systems(
+            Update,
+            (
+                handle_trade_action,
+                handle_propose_trade,
+                answer_trade_as_ai,
+                handle_answer_trade,
+                cancel_trade_after_phase,
+                update_trade_ui,
+            )
+                .chain()
+                // The server doesn't know about trades: online, the panel
+                // stays hidden and nothing can be traded.
+                .run_if(in_state(Screen::Playing).and_then(not(resource_exists::<NetClient>))),
+        );
+}
+
+/// Cash added to an offer per button press.
+const CASH_STEP: i32 = 500;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TradeGoods {
+    pub cocaine: usize,
+    pub cannabis: usize,
+    pub cash: i32,
+}
+
+impl TradeGoods {
+    pub fn is_empty(&self) -> bool {
+        self.cocaine == 0 && self.cannabis == 0 && self.cash == 0
+    }
+
+    pub fn chips(&self, chip_type: ChipType) -> usize {
+        match chip_type {
+            ChipType::Cocaine => self.cocaine,
+            ChipType::Cannabis => self.cannabis,
+        }
+    }
+
+    pub fn value(&self) -> i32 {
+        self.cocaine as i32 * ChipType::Cocaine.price()
+            + self.cannabis as i32 * ChipType::Cannabis.price()
+            + self.cash
+    }
+
+    pub fn describe(&self, locale: &Locale) -> String {
+        let mut parts = vec![];
+        for (key, count) in [
+            ("trade-goods-cocaine", self.cocaine),
+            ("trade-goods-cannabis", self.cannabis),
+        ] {
+            if count > 0 {
+                parts.push(locale.format(key, &[("count", count.to_string())]));
+            }
+        }
+        if self.cash > 0 {
+            parts.push(locale.money(self.cash));
+        }
+        if parts.is_empty() {
+            locale.get("trade-goods-nothing")
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// Why an offer can't go through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeProblem {
+    NoTarget,
+    Empty,
+    CantAfford(usize, i32),
+    NotEnough(usize, ChipType),
+    NoRoom(usize),
+}
+
+impl TradeProblem {
+    pub fn describe(&self, locale: &Locale, match_settings: &MatchSettings) -> String {
+        let name = |player: &usize| ("name", match_settings.player(*player).name.clone());
+        match self {
+            TradeProblem::NoTarget => locale.get("trade-problem-no-target"),
+            TradeProblem::Empty => locale.get("trade-problem-empty"),
+            TradeProblem::CantAfford(player, cash) => locale.format(
+                "trade-problem-cant-afford",
+                &[name(player), ("amount", locale.money(*cash))],
+            ),
+            TradeProblem::NotEnough(player, chip_type) => locale.format(
+                "trade-problem-not-enough",
+                &[name(player), ("chip", locale.name_of("chip", chip_type))],
+            ),
+            TradeProblem::NoRoom(player) => locale.format("trade-problem-no-room", &[name(player)]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TradeOffer {
+    pub from: usize,
+    pub to: usize,
+    pub give: TradeGoods,
+    pub take: TradeGoods,
+}
+
+/// Offer being drafted on the trade panel and the one awaiting an answer.
+#[derive(Resource, Default)]
+pub struct TradeDesk {
+    pub target: usize,
+    pub give: TradeGoods,
+    pub take: TradeGoods,
+    pub pending: Option<TradeOffer>,
+}
+
+impl TradeDesk {
+    fn clear_draft(&mut self) {
+        self.give = TradeGoods::default();
+        self.take = TradeGoods::default();
+    }
+}
+
+#[derive(Event)]
+pub struct ProposeTrade(pub TradeOffer);
+
+#[derive(Event)]
+pub struct AnswerTrade {
+    pub accept: bool,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+pub enum TradeUIAction {
+    ContainerTrade,
+    ButtonCycleTarget,
+    ButtonGiveCocaine,
+    ButtonGiveCannabis,
+    ButtonGiveCash,
+    ButtonTakeCocaine,
+    ButtonTakeCannabis,
+    ButtonTakeCash,
+    ButtonClear,
+    ButtonPropose,
+    LabelOffer,
+    ButtonAccept,
+    ButtonReject,
+}
+
+impl TradeUIAction {
+    /// Label of the buttons that show the amount they set.
+    fn key(self) -> Option<&'static str> {
+        match self {
+            TradeUIAction::ButtonCycleTarget => Some("trade-with"),
+            TradeUIAction::ButtonGiveCocaine => Some("trade-give-cocaine"),
+            TradeUIAction::ButtonGiveCannabis => Some("trade-give-cannabis"),
+            TradeUIAction::ButtonGiveCash => Some("trade-give-cash"),
+            TradeUIAction::ButtonTakeCocaine => Some("trade-ask-cocaine"),
+            TradeUIAction::ButtonTakeCannabis => Some("trade-ask-cannabis"),
+            TradeUIAction::ButtonTakeCash => Some("trade-ask-cash"),
+            _ => None,
+        }
+    }
+}
+
+/// Chips a player can trade away: production first, then sales.
+fn tradable_chips(
+    chips: &Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+    player: usize,
+    chip_type: ChipType,
+) -> Vec<Entity> {
+    let mut tradable = chips
+        .iter()
+        .filter(|(_, chip, area)| {
+            area.player == player && chip.data == chip_type && (1..=2).contains(&area.marker)
+        })
+        .map(|(entity, _, area)| (area.marker, entity))
+        .collect::<Vec<_>>();
+    tradable.sort_by_key(|(marker, _)| *marker);
+    tradable.into_iter().map(|(_, entity)| entity).collect()
+}
+
+/// The chips `giver` hands over for `goods`.
+fn traded_chips(
+    chips: &Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+    goods: &TradeGoods,
+    giver: usize,
+) -> Vec<Entity> {
+    [ChipType::Cocaine, ChipType::Cannabis]
+        .into_iter()
+        .flat_map(|chip_type| {
+            tradable_chips(chips, giver, chip_type)
+                .into_iter()
+                .take(goods.chips(chip_type))
+        })
+        .collect()
+}
+
+/// Traded chips keep their area, which must have room for them at the
+/// receiver once the chips going the other way are gone.
+fn validate_room(
+    goods: &TradeGoods,
+    giver: usize,
+    returned: &TradeGoods,
+    receiver: usize,
+    state: &GameState,
+    chips: &Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+) -> Result<(), TradeProblem> {
+    let in_area = |entities: &[Entity], marker: usize| {
+        entities
+            .iter()
+            .filter(|entity| {
+                chips
+                    .get(**entity)
+                    .is_ok_and(|(_, _, area)| area.marker == marker)
+            })
+            .count()
+    };
+    let arriving = traded_chips(chips, goods, giver);
+    let leaving = traded_chips(chips, returned, receiver);
+    for marker in [1, 2] {
+        let arriving = in_area(&arriving, marker);
+        if arriving == 0 {
+            continue;
+        }
+        let held = chips
+            .iter()
+            .filter(|(_, _, area)| area.player == receiver && area.marker == marker)
+            .count();
+        let after = held + arriving - in_area(&leaving, marker);
+        if !state.has_room(receiver, marker, after - 1) {
+            return Err(TradeProblem::NoRoom(receiver));
+        }
+    }
+    Ok(())
+}
+
+fn validate_goods(
+    goods: &TradeGoods,
+    player: usize,
+    state: &GameState,
+    chips: &Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+) -> Result<(), TradeProblem> {
+    if goods.cash > 0 && goods.cash > state.get_balance(player) {
+        return Err(TradeProblem::CantAfford(player, goods.cash));
+    }
+    for chip_type in [ChipType::Cocaine, ChipType::Cannabis] {
+        if goods.chips(chip_type) > tradable_chips(chips, player, chip_type).len() {
+            return Err(TradeProblem::NotEnough(player, chip_type));
+        }
+    }
+    Ok(())
+}
+
+fn validate_offer(
+    offer: &TradeOffer,
+    state: &GameState,
+    chips: &Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+) -> Result<(), TradeProblem> {
+    if offer.from == offer.to || offer.to == 0 || offer.to > state.num_players() {
+        return Err(TradeProblem::NoTarget);
+    }
+    if offer.give.is_empty() && offer.take.is_empty() {
+        return Err(TradeProblem::Empty);
+    }
+    validate_goods(&offer.give, offer.from, state, chips)?;
+    validate_goods(&offer.take, offer.to, state, chips)?;
+    validate_room(&offer.give, offer.from, &offer.take, offer.to, state, chips)?;
+    validate_room(&offer.take, offer.to, &offer.give, offer.from, state, chips)
+}
+
+fn spawn_trade_ui(_trigger: Trigger<SpawnBoard>, mut commands: Commands, locale: Res<Locale>) {
+    commands
+        .spawn((
+            Name::new("Trade Panel"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Px(216.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::FlexStart,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(10.0),
+                    left: Val::Px(0.0),
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TradeUIAction::ContainerTrade,
+        ))
+        .insert(StateScoped(Screen::Playing))
+        .with_children(|children| {
+            children
+                .label(locale.get("trade-header"))
+                .insert(Localized("trade-header"));
+            // Buttons showing an amount get their text in `update_trade_ui`.
+            for action in [
+                TradeUIAction::ButtonCycleTarget,
+                TradeUIAction::ButtonGiveCocaine,
+                TradeUIAction::ButtonGiveCannabis,
+                TradeUIAction::ButtonGiveCash,
+                TradeUIAction::ButtonTakeCocaine,
+                TradeUIAction::ButtonTakeCannabis,
+                TradeUIAction::ButtonTakeCash,
+            ] {
+                if let Some(key) = action.key() {
+                    children.button(locale.get(key)).insert(action);
+                }
+            }
+            for (action, key) in [
+                (TradeUIAction::ButtonClear, "trade-clear"),
+                (TradeUIAction::ButtonPropose, "trade-propose"),
+            ] {
+                children
+                    .button(locale.get(key))
+                    .insert((action, Localized(key)));
+            }
+            children.label("").insert(TradeUIAction::LabelOffer);
+            for (action, key) in [
+                (TradeUIAction::ButtonAccept, "trade-accept"),
+                (TradeUIAction::ButtonReject, "trade-reject"),
+            ] {
+                children
+                    .button(locale.get(key))
+                    .insert((action, Localized(key)));
+            }
+        });
+}
+
+fn handle_trade_action(
+    mut button_query: InteractionQuery<&TradeUIAction>,
+    mut desk: ResMut<TradeDesk>,
+    mut ew_propose_trade: EventWriter<ProposeTrade>,
+    mut ew_answer_trade: EventWriter<AnswerTrade>,
+    state: Res<GameState>,
+    tutorial: Option<Res<Tutorial>>,
+) {
+    // No tutorial step involves trading.
+    if tutorial
+        .as_ref()
+        .is_some_and(|tutorial| !tutorial.allows_free_play())
+    {
+        return;
+    }
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        match action {
+            TradeUIAction::ButtonCycleTarget => {
+                let mut target = state.next_player(desk.target.max(1));
+                if target == state.player {
+                    target = state.next_player(target);
+                }
+                desk.target = target;
+            }
+            TradeUIAction::ButtonGiveCocaine => desk.give.cocaine += 1,
+            TradeUIAction::ButtonGiveCannabis => desk.give.cannabis += 1,
+            TradeUIAction::ButtonGiveCash => desk.give.cash += CASH_STEP,
+            TradeUIAction::ButtonTakeCocaine => desk.take.cocaine += 1,
+            TradeUIAction::ButtonTakeCannabis => desk.take.cannabis += 1,
+            TradeUIAction::ButtonTakeCash => desk.take.cash += CASH_STEP,
+            TradeUIAction::ButtonClear => desk.clear_draft(),
+            TradeUIAction::ButtonPropose => {
+                ew_propose_trade.send(ProposeTrade(TradeOffer {
+                    from: state.player,
+                    to: desk.target,
+                    give: desk.give,
+                    take: desk.take,
+                }));
+            }
+            TradeUIAction::ButtonAccept => {
+                ew_answer_trade.send(AnswerTrade { accept: true });
+            }
+            TradeUIAction::ButtonReject => {
+                ew_answer_trade.send(AnswerTrade { accept: false });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn handle_propose_trade(
+    mut er_propose_trade: EventReader<ProposeTrade>,
+    mut desk: ResMut<TradeDesk>,
+    mut log: ResMut<GameLog>,
+    state: Res<GameState>,
+    chips: Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+) {
+    for ProposeTrade(offer) in er_propose_trade.read() {
+        if state.phase != TurnPhase::PlaceCardsOnTable || desk.pending.is_some() {
+            continue;
+        }
+
+        let text = match validate_offer(offer, &state, &chips) {
+            Ok(()) => {
+                desk.pending = Some(*offer);
+                locale.format(
+                    "log-trade-offer",
+                    &[
+                        ("name", match_settings.player(offer.from).name.clone()),
+                        ("target", match_settings.player(offer.to).name.clone()),
+                        ("give", offer.give.describe(&locale)),
+                        ("take", offer.take.describe(&locale)),
+                    ],
+                )
+            }
+            Err(problem) => locale.format(
+                "log-trade-refused",
+                &[("reason", problem.describe(&locale, &match_settings))],
+            ),
+        };
+        log.push(state.turn_number, text);
+    }
+}
+
+/// Computer players take any trade that leaves them at least as well off.
+fn answer_trade_as_ai(
+    desk: Res<TradeDesk>,
+    match_settings: Res<MatchSettings>,
+    mut ew_answer_trade: EventWriter<AnswerTrade>,
+) {
+    let Some(offer) = desk.pending else {
+        return;
+    };
+    if !desk.is_changed() || !match_settings.is_ai(offer.to) {
+        return;
+    }
+
+    ew_answer_trade.send(AnswerTrade {
+        accept: offer.give.value() >= offer.take.value(),
+    });
+}
+
+fn handle_answer_trade(
+    mut er_answer_trade: EventReader<AnswerTrade>,
+    mut desk: ResMut<TradeDesk>,
+    mut log: ResMut<GameLog>,
+    mut state: ResMut<GameState>,
+    mut ew_transfer_chip: EventWriter<TransferChip>,
+    chips: Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+) {
+    for answer in er_answer_trade.read() {
+        let Some(offer) = desk.pending.take() else {
+            continue;
+        };
+        let from_name = ("name", match_settings.player(offer.from).name.clone());
+        let to_name = ("target", match_settings.player(offer.to).name.clone());
+
+        if !answer.accept {
+            let text = locale.format("log-trade-rejected", &[from_name, to_name]);
+            log.push(state.turn_number, text);
+            continue;
+        }
+
+        // Inventories may have changed while the offer was pending.
+        if let Err(problem) = validate_offer(&offer, &state, &chips) {
+            let text = locale.format(
+                "log-trade-failed",
+                &[("reason", problem.describe(&locale, &match_settings))],
+            );
+            log.push(state.turn_number, text);
+            continue;
+        }
+
+        for (goods, giver, receiver) in [
+            (offer.give, offer.from, offer.to),
+            (offer.take, offer.to, offer.from),
+        ] {
+            state.change_balance(giver, -goods.cash);
+            state.change_balance(receiver, goods.cash);
+
+            for entity in traded_chips(&chips, &goods, giver) {
+                ew_transfer_chip.send(TransferChip {
+                    entity,
+                    player: receiver,
+                });
+            }
+        }
+
+        let text = locale.format(
+            "log-traded",
+            &[
+                from_name,
+                to_name,
+                ("give", offer.give.describe(&locale)),
+                ("take", offer.take.describe(&locale)),
+            ],
+        );
+        log.push(state.turn_number, text);
+        desk.clear_draft();
+    }
+}
+
+fn cancel_trade_after_phase(mut desk: ResMut<TradeDesk>, state: Res<GameState>) {
+    if state.phase != TurnPhase::PlaceCardsOnTable && desk.pending.is_some() {
+        desk.pending = None;
+    }
+}
+
+fn update_trade_ui(
+    desk: Res<TradeDesk>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    mut ui_query: Query<(&TradeUIAction, &mut Visibility, Option<&Children>)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !desk.is_changed() && !state.is_changed() {
+        return;
+    }
+
+    let trading = state.phase == TurnPhase::PlaceCardsOnTable;
+    let drafting = trading && desk.pending.is_none() && !match_settings.is_ai(state.player);
+    let answering = desk
+        .pending
+        .is_some_and(|offer| !match_settings.is_ai(offer.to));
+
+    for (action, mut visibility, children) in &mut ui_query {
+        let visible = match action {
+            TradeUIAction::ContainerTrade => drafting || answering,
+            TradeUIAction::LabelOffer => answering,
+            TradeUIAction::ButtonAccept | TradeUIAction::ButtonReject => answering,
+            _ => drafting,
+        };
+        *visibility = if visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        let Some(&child) = children
+            .into_iter()
+            .flatten()
+            .find(|child| text_query.contains(**child))
+        else {
+            continue;
+        };
+        let mut text = text_query.get_mut(child).unwrap();
+        let target_name = match desk.target {
+            0 => "-".to_string(),
+            target => match_settings.player(target).name.clone(),
+        };
+
+        let value = match action {
+            TradeUIAction::ButtonCycleTarget => target_name,
+            TradeUIAction::ButtonGiveCocaine => desk.give.cocaine.to_string(),
+            TradeUIAction::ButtonGiveCannabis => desk.give.cannabis.to_string(),
+            TradeUIAction::ButtonGiveCash => locale.money(desk.give.cash),
+            TradeUIAction::ButtonTakeCocaine => desk.take.cocaine.to_string(),
+            TradeUIAction::ButtonTakeCannabis => desk.take.cannabis.to_string(),
+            TradeUIAction::ButtonTakeCash => locale.money(desk.take.cash),
+            TradeUIAction::LabelOffer => {
+                text.sections[0].value = match desk.pending {
+                    Some(offer) => locale.format(
+                        "trade-offer",
+                        &[
+                            ("target", match_settings.player(offer.to).name.clone()),
+                            ("name", match_settings.player(offer.from).name.clone()),
+                            ("give", offer.give.describe(&locale)),
+                            ("take", offer.take.describe(&locale)),
+                        ],
+                    ),
+                    None => String::new(),
+                };
+                continue;
+            }
+            _ => continue,
+        };
+        let Some(key) = action.key() else {
+            continue;
+        };
+        text.sections[0].value = format!("{}: {}", locale.get(key), value);
+    }
+}
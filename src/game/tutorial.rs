@@ -0,0 +1,267 @@
+//! Guided first turn that teaches the flow of the game step by step.
+//!
+//! While a [`Tutorial`] is running, only the action asked for by the current
+//! step is accepted; the step advances as soon as the player performs it.
+
+use bevy::prelude::*;
+use bevy_la_mesa::{events::DeckShuffle, Card, CardOnTable, Hand, PlayArea};
+
+use super::{
+    cards::{CardType, GameState, Kard, TurnPhase},
+    locale::Locale,
+    spawn::{level::SpawnBoard, ui::CardGameUIAction},
+};
+use crate::screen::Screen;
+
+pub(super) fn plugin(app: &mut App) {
+    app.observe(spawn_tutorial_ui).add_systems(
+        Update,
+        (
+            advance_tutorial,
+            update_tutorial_text,
+            highlight_buttons,
+            highlight_play_areas,
+        )
+            .chain()
+            .run_if(in_state(Screen::Playing).and_then(resource_exists::<Tutorial>)),
+    );
+}
+
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TutorialStep {
+    #[default]
+    Shuffle,
+    Draw,
+    PlayProduction,
+    PlayTransport,
+    PlaySales,
+    EndPlacement,
+    ResolveEvent,
+    Done,
+}
+
+impl TutorialStep {
+    fn next(self) -> Self {
+        match self {
+            TutorialStep::Shuffle => TutorialStep::Draw,
+            TutorialStep::Draw => TutorialStep::PlayProduction,
+            TutorialStep::PlayProduction => TutorialStep::PlayTransport,
+            TutorialStep::PlayTransport => TutorialStep::PlaySales,
+            TutorialStep::PlaySales => TutorialStep::EndPlacement,
+            TutorialStep::EndPlacement => TutorialStep::ResolveEvent,
+            TutorialStep::ResolveEvent | TutorialStep::Done => TutorialStep::Done,
+        }
+    }
+
+    /// Button the player is expected to press.
+    fn button(self) -> Option<CardGameUIAction> {
+        match self {
+            TutorialStep::Shuffle => Some(CardGameUIAction::ButtonShuffleDeck),
+            TutorialStep::Draw => Some(CardGameUIAction::ButtonDrawHand),
+            TutorialStep::EndPlacement => Some(CardGameUIAction::ButtonAdvancePhase),
+            _ => None,
+        }
+    }
+
+    /// Cards the player is expected to play.
+    fn cards(self) -> &'static [CardType] {
+        match self {
+            TutorialStep::PlayProduction => &[CardType::Cocaine, CardType::Cannabis],
+            TutorialStep::PlayTransport => &[CardType::Truck, CardType::Train],
+            TutorialStep::PlaySales => &[CardType::Export, CardType::LocalMarket],
+            _ => &[],
+        }
+    }
+
+    /// Play area markers to point at; 6 is the event slot.
+    fn play_areas(self) -> &'static [usize] {
+        match self {
+            TutorialStep::PlayProduction
+            | TutorialStep::PlayTransport
+            | TutorialStep::PlaySales => &[1, 2, 3, 4, 5],
+            TutorialStep::ResolveEvent => &[6],
+            _ => &[],
+        }
+    }
+}
+
+/// Present while the tutorial runs, removed once it is over.
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    pub step: TutorialStep,
+}
+
+impl Tutorial {
+    /// Whether a button may be used during the current step.
+    pub fn allows_button(&self, action: CardGameUIAction) -> bool {
+        self.step == TutorialStep::Done || self.step.button() == Some(action)
+    }
+
+    /// Whether a card may be played during the current step.
+    pub fn allows_card(&self, card_type: CardType) -> bool {
+        self.step == TutorialStep::Done || self.step.cards().contains(&card_type)
+    }
+
+    /// Whether moves no step asks for, such as trading or taking a card back
+    /// from the table, may be made.
+    pub fn allows_free_play(&self) -> bool {
+        self.step == TutorialStep::Done
+    }
+}
+
+#[derive(Component)]
+struct TutorialPanel;
+
+#[derive(Component)]
+struct TutorialText;
+
+fn spawn_tutorial_ui(
+    _trigger: Trigger<SpawnBoard>,
+    mut commands: Commands,
+    tutorial: Option<Res<Tutorial>>,
+    locale: Res<Locale>,
+) {
+    if tutorial.is_none() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Name::new("Tutorial"),
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(50.0),
+                    top: Val::Px(10.0),
+                    left: Val::Percent(25.0),
+                    padding: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                background_color: BackgroundColor::from(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                ..default()
+            },
+            StateScoped(Screen::Playing),
+            TutorialPanel,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.name_of("tutorial", TutorialStep::default()),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                TutorialText,
+            ));
+        });
+}
+
+fn advance_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    mut er_shuffle: EventReader<DeckShuffle>,
+    state: Res<GameState>,
+    cards_on_table: Query<(&Card<Kard>, &CardOnTable)>,
+    cards_in_hand: Query<(&Card<Kard>, &Hand)>,
+    panels: Query<Entity, With<TutorialPanel>>,
+) {
+    let shuffled = er_shuffle.read().count() > 0;
+    let played = |card_types: &[CardType]| {
+        cards_on_table.iter().any(|(card, card_on_table)| {
+            card_on_table.player == 1 && card_types.contains(&card.data.card_type)
+        })
+    };
+    // Steps asking for a card the shuffle didn't deal are skipped.
+    let missing = |card_types: &[CardType]| {
+        let hand = cards_in_hand
+            .iter()
+            .filter(|(_, hand)| hand.player == 1)
+            .collect::<Vec<_>>();
+        !hand.is_empty()
+            && !hand
+                .iter()
+                .any(|(card, _)| card_types.contains(&card.data.card_type))
+    };
+
+    let step_done = match tutorial.step {
+        TutorialStep::Shuffle => shuffled,
+        TutorialStep::Draw => state.phase == TurnPhase::PlaceCardsOnTable,
+        TutorialStep::PlayProduction | TutorialStep::PlayTransport | TutorialStep::PlaySales => {
+            played(tutorial.step.cards()) || missing(tutorial.step.cards())
+        }
+        TutorialStep::EndPlacement => state.phase != TurnPhase::PlaceCardsOnTable,
+        // The first turn is over once the second seat takes over.
+        TutorialStep::ResolveEvent => state.player != 1,
+        TutorialStep::Done => false,
+    };
+
+    if step_done {
+        tutorial.step = tutorial.step.next();
+    }
+
+    // From here on the game goes on as a regular scenario.
+    if tutorial.step == TutorialStep::Done && state.player == 1 && state.turn_number > 1 {
+        commands.remove_resource::<Tutorial>();
+        for panel in &panels {
+            commands.entity(panel).despawn_recursive();
+        }
+    }
+}
+
+fn update_tutorial_text(
+    tutorial: Res<Tutorial>,
+    locale: Res<Locale>,
+    mut text_query: Query<&mut Text, With<TutorialText>>,
+) {
+    if !tutorial.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = locale.name_of("tutorial", tutorial.step);
+    }
+}
+
+fn highlight_buttons(
+    mut commands: Commands,
+    tutorial: Res<Tutorial>,
+    buttons: Query<(Entity, &CardGameUIAction), With<Button>>,
+    spawned_query: Query<(), Added<CardGameUIAction>>,
+) {
+    if !tutorial.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+    for (entity, action) in &buttons {
+        if tutorial.step.button() == Some(*action) {
+            commands
+                .entity(entity)
+                .insert(Outline::new(Val::Px(4.0), Val::ZERO, HIGHLIGHT_COLOR));
+        } else {
+            commands.entity(entity).remove::<Outline>();
+        }
+    }
+}
+
+fn highlight_play_areas(
+    mut gizmos: Gizmos,
+    tutorial: Res<Tutorial>,
+    play_areas: Query<(&GlobalTransform, &PlayArea)>,
+) {
+    let markers = tutorial.step.play_areas();
+    for (transform, play_area) in &play_areas {
+        if play_area.player != 1 || !markers.contains(&play_area.marker) {
+            continue;
+        }
+        let (_, rotation, translation) = transform.to_scale_rotation_translation();
+        gizmos.rect(
+            translation + Vec3::Y * 0.05,
+            rotation * Quat::from_rotation_x(std::f32::consts::FRAC_PI_2),
+            Vec2::new(2.5, 3.5),
+            HIGHLIGHT_COLOR,
+        );
+    }
+}
@@ -1,24 +1,71 @@
 use bevy::prelude::*;
 use bevy_la_mesa::{
     events::{DeckShuffle, DrawHand},
-    Chip, ChipArea,
+    Chip, ChipArea, LaMesaPluginSettings,
 };
 
 use super::{
+    campaign::ActiveScenario,
     cards::{
-        AdvancePhase, ChipType, DropChip, GameOver, GameState, MoveChip, SwitchPlayer, TurnPhase,
+        AdvancePhase, ChipType, DropChip, GameOver, GameState, InformantReport, Market, MoveChip,
+        SwitchPlayer, TurnPhase, MAX_HEAT,
     },
+    locale::Locale,
+    settings::MatchSettings,
     spawn::ui::{self, CardGameUIAction},
+    spectator::Spectator,
+    tutorial::Tutorial,
+};
+use crate::{
+    net::{client::NetClient, protocol::ClientMessage},
+    ui::prelude::InteractionQuery,
 };
-use crate::ui::prelude::InteractionQuery;
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<InformantIntel>();
     app.add_systems(
         Update,
-        (handle_gameplay_action, handle_labels, handle_game_over),
+        (
+            handle_gameplay_action,
+            keep_informant_report,
+            handle_labels,
+            handle_game_over,
+        )
+            .chain(),
     );
 }
 
+/// Latest Informant report for a seat played on this screen, shown until the
+/// end of that player's turn.
+#[derive(Resource, Default)]
+struct InformantIntel(Option<InformantReport>);
+
+fn keep_informant_report(
+    mut er_informant_report: EventReader<InformantReport>,
+    mut intel: ResMut<InformantIntel>,
+    state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    spectator: Option<Res<Spectator>>,
+    net: Option<Res<NetClient>>,
+) {
+    for report in er_informant_report.read() {
+        let local = match net.as_deref() {
+            Some(net) => net.player == Some(report.player),
+            None => spectator.is_none() && !match_settings.is_ai(report.player),
+        };
+        if local {
+            intel.0 = Some(report.clone());
+        }
+    }
+    if intel
+        .0
+        .as_ref()
+        .is_some_and(|report| report.player != state.player)
+    {
+        intel.0 = None;
+    }
+}
+
 fn handle_gameplay_action(
     mut button_query: InteractionQuery<&CardGameUIAction>,
     mut ew_shuffle: EventWriter<DeckShuffle>,
@@ -28,19 +75,62 @@ fn handle_gameplay_action(
     mut ew_move_chip: EventWriter<MoveChip>,
     mut ew_switch_player: EventWriter<SwitchPlayer>,
     chips: Query<(Entity, &Transform, &Chip<ChipType>, &ChipArea)>,
-    state: Res<GameState>,
+    mut state: ResMut<GameState>,
+    match_settings: Res<MatchSettings>,
+    plugin_settings: Res<LaMesaPluginSettings>,
+    tutorial: Option<Res<Tutorial>>,
+    net: Option<Res<NetClient>>,
 ) {
+    // Seats played by the computer ignore the buttons.
+    if match_settings.is_ai(state.player) {
+        return;
+    }
+
+    // Online, the server decides; only the seat whose turn it is may act.
+    if let Some(net) = net {
+        if !net.is_my_turn(&state) {
+            return;
+        }
+        for (interaction, action) in &mut button_query {
+            if !matches!(interaction, Interaction::Pressed) {
+                continue;
+            }
+            let message = match action {
+                CardGameUIAction::ButtonDrawHand => ClientMessage::DrawHand,
+                CardGameUIAction::ButtonAdvancePhase => ClientMessage::AdvancePhase,
+                CardGameUIAction::ButtonBorrow => ClientMessage::Borrow,
+                CardGameUIAction::ButtonRepay => ClientMessage::Repay,
+                CardGameUIAction::ButtonCargo => ClientMessage::CycleCargo,
+                _ => continue,
+            };
+            net.send(message);
+        }
+        return;
+    }
+
     for (interaction, action) in &mut button_query {
+        if tutorial
+            .as_ref()
+            .is_some_and(|tutorial| !tutorial.allows_button(*action))
+        {
+            continue;
+        }
         if matches!(interaction, Interaction::Pressed) {
             match action {
                 CardGameUIAction::ButtonShuffleDeck => {
-                    ew_shuffle.send(DeckShuffle { deck_marker: 1 });
-                    ew_shuffle.send(DeckShuffle { deck_marker: 2 });
+                    let mut deck_markers = (1..=state.num_players())
+                        .map(|player| state.playing_deck(player))
+                        .collect::<Vec<_>>();
+                    deck_markers.dedup();
+                    deck_markers.push(2);
+                    for deck_marker in deck_markers {
+                        ew_shuffle.send(DeckShuffle { deck_marker });
+                    }
                 }
                 CardGameUIAction::ButtonDrawHand => {
                     let event = DrawHand {
-                        deck_marker: 1,
-                        num_cards: 5,
+                        deck_marker: state.playing_deck(state.player),
+                        num_cards: plugin_settings.hand_size,
                         player: state.player,
                     };
                     ew_draw.send(event);
@@ -90,13 +180,25 @@ fn handle_gameplay_action(
                 }
                 CardGameUIAction::ButtonSwitchPlayer => {
                     ew_switch_player.send(SwitchPlayer {
-                        player: match state.player {
-                            1 => 2,
-                            2 => 1,
-                            _ => 1,
-                        },
+                        player: state.next_player(state.player),
                     });
                 }
+                CardGameUIAction::ButtonBorrow => {
+                    if state.phase == TurnPhase::Prepare {
+                        let player = state.player;
+                        state.borrow(player);
+                    }
+                }
+                CardGameUIAction::ButtonRepay => {
+                    if state.phase == TurnPhase::Prepare {
+                        let player = state.player;
+                        state.repay(player);
+                    }
+                }
+                CardGameUIAction::ButtonCargo => {
+                    let player = state.player;
+                    state.cycle_cargo(player);
+                }
                 _ => {}
             }
         }
@@ -107,6 +209,9 @@ fn handle_labels(
     mut label_query: Query<(Entity, &mut Visibility, &CardGameUIAction)>,
     mut text_query: Query<(&Parent, &mut Text)>,
     state: Res<GameState>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    intel: Res<InformantIntel>,
 ) {
     for (entity, mut visibility, ui_element) in &mut label_query {
         for (parent, mut text) in text_query.iter_mut() {
@@ -115,10 +220,14 @@ fn handle_labels(
             }
             match ui_element {
                 CardGameUIAction::LabelTurnNumber => {
-                    text.sections[0].value = format!("Turn number: {}", state.turn_number);
+                    text.sections[0].value =
+                        locale.format("ui-turn", &[("turn", state.turn_number.to_string())]);
                 }
                 CardGameUIAction::LabelTurnPhase => {
-                    text.sections[0].value = format!("Turn phase: {:?}", state.phase);
+                    text.sections[0].value = locale.format(
+                        "ui-phase",
+                        &[("phase", locale.name_of("phase", state.phase))],
+                    );
                 }
                 CardGameUIAction::ButtonShuffleDeck => {
                     if state.phase == TurnPhase::Prepare {
@@ -134,38 +243,130 @@ fn handle_labels(
                         *visibility = Visibility::Hidden;
                     }
                 }
+                CardGameUIAction::ButtonBorrow => {
+                    if state.phase == TurnPhase::Prepare && state.can_borrow(state.player) {
+                        *visibility = Visibility::Visible;
+                    } else {
+                        *visibility = Visibility::Hidden;
+                    }
+                }
+                CardGameUIAction::ButtonRepay => {
+                    if state.phase == TurnPhase::Prepare && state.get_debt(state.player) > 0 {
+                        *visibility = Visibility::Visible;
+                    } else {
+                        *visibility = Visibility::Hidden;
+                    }
+                }
                 CardGameUIAction::LabelPhaseDescription => {
-                    text.sections[0].value = match state.phase {
-                        TurnPhase::Prepare => {
-                            "You may shuffle the deck and draw 5 cards".to_string()
-                        }
-                        TurnPhase::PlaceCardsOnTable => {
-                            "You may play cards from your hand or draw".to_string()
-                        }
-                        TurnPhase::DrawEventCard => "Drawing event card".to_string(),
-                        TurnPhase::ApplyEventCard => "Applying event card effects".to_string(),
-                        TurnPhase::End => "Update your counters and pass turn".to_string(),
-                        TurnPhase::ApplyProductionCards => "Applying Production Cards".to_string(),
-                        TurnPhase::ApplyTransportationCards => {
-                            "Applying Transportation Cards".to_string()
-                        }
-                        TurnPhase::ApplySalesCards => "Applying Sales Cards".to_string(),
-                        TurnPhase::ApplyActionCards => "Applying Action Cards".to_string(),
-                    };
+                    text.sections[0].value = locale.phase_hint(state.phase);
                 }
                 CardGameUIAction::ButtonDropChip => {}
                 CardGameUIAction::ButtonMoveChip => {}
                 CardGameUIAction::ButtonAdvancePhase => {}
                 CardGameUIAction::LabelPlayerNumber => {
-                    text.sections[0].value = format!("Player number: {}", state.player)
+                    text.sections[0].value = locale.format(
+                        "ui-player",
+                        &[("name", match_settings.player(state.player).name.clone())],
+                    );
                 }
                 CardGameUIAction::ButtonSwitchPlayer => {}
                 CardGameUIAction::LabelBank => {
-                    text.sections[0].value = format!("Bank: ${}", state.get_balance(state.player));
+                    text.sections[0].value = locale.format(
+                        "ui-bank",
+                        &[("amount", locale.money(state.get_balance(state.player)))],
+                    );
+                }
+                CardGameUIAction::ButtonCargo => {
+                    if state.phase == TurnPhase::PlaceCardsOnTable {
+                        *visibility = Visibility::Visible;
+                    } else {
+                        *visibility = Visibility::Hidden;
+                    }
+                    text.sections[0].value = locale.format(
+                        "ui-load",
+                        &[(
+                            "cargo",
+                            locale.name_of("cargo", state.get_cargo(state.player)),
+                        )],
+                    );
+                }
+                CardGameUIAction::LabelDebt => {
+                    text.sections[0].value = locale.format(
+                        "ui-debt",
+                        &[("amount", locale.money(state.get_debt(state.player)))],
+                    );
+                }
+                CardGameUIAction::LabelHeat => {
+                    text.sections[0].value = locale.format(
+                        "ui-heat",
+                        &[
+                            ("heat", state.get_heat(state.player).to_string()),
+                            ("max", MAX_HEAT.to_string()),
+                        ],
+                    );
+                }
+                CardGameUIAction::LabelMarkets => {
+                    text.sections[0].value = [Market::Export, Market::Local]
+                        .iter()
+                        .map(|market| {
+                            locale.format(
+                                "ui-demand",
+                                &[
+                                    ("market", locale.name_of("market", market)),
+                                    (
+                                        "cocaine",
+                                        state
+                                            .market_demand_left(*market, ChipType::Cocaine)
+                                            .to_string(),
+                                    ),
+                                    (
+                                        "cannabis",
+                                        state
+                                            .market_demand_left(*market, ChipType::Cannabis)
+                                            .to_string(),
+                                    ),
+                                ],
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
                 }
                 CardGameUIAction::LabelEffects => {
-                    text.sections[0].value =
-                        format!("Effects: {:?}", state.get_effects(state.player));
+                    let effects = state
+                        .get_effects(state.player)
+                        .iter()
+                        .map(|effect| locale.name_of("effect", effect.effect_type))
+                        .collect::<Vec<_>>();
+                    let effects = if effects.is_empty() {
+                        locale.get("ui-effects-none")
+                    } else {
+                        effects.join(", ")
+                    };
+                    text.sections[0].value = locale.format("ui-effects", &[("effects", effects)]);
+                }
+                CardGameUIAction::LabelInformant => {
+                    let Some(report) = intel.0.as_ref() else {
+                        *visibility = Visibility::Hidden;
+                        continue;
+                    };
+                    *visibility = Visibility::Visible;
+                    let cards = report
+                        .cards
+                        .iter()
+                        .map(|card_type| locale.card_name(*card_type))
+                        .collect::<Vec<_>>();
+                    let cards = if cards.is_empty() {
+                        locale.get("ui-informant-empty")
+                    } else {
+                        cards.join(", ")
+                    };
+                    text.sections[0].value = locale.format(
+                        "ui-informant",
+                        &[
+                            ("name", match_settings.player(report.target).name.clone()),
+                            ("cards", cards),
+                        ],
+                    );
                 }
                 _ => {}
             }
@@ -177,6 +378,9 @@ pub fn handle_game_over(
     mut query: Query<(&CardGameUIAction, &mut Text, &mut Visibility), With<Text>>,
     mut game_over_container_visibility: Query<(&CardGameUIAction, &mut Visibility), Without<Text>>,
     mut er_game_over: EventReader<GameOver>,
+    match_settings: Res<MatchSettings>,
+    scenario: Option<Res<ActiveScenario>>,
+    locale: Res<Locale>,
 ) {
     for event in er_game_over.read() {
         for (ui_action, mut text, mut visibility) in query.iter_mut() {
@@ -184,7 +388,20 @@ pub fn handle_game_over(
                 continue;
             }
             *visibility = Visibility::Visible;
-            text.sections[0].value = format!("Game Over; {} player won!", event.player_won);
+            text.sections[0].value = match scenario.as_deref() {
+                Some(active) if event.player_won == 1 => locale.format(
+                    "ui-scenario-complete",
+                    &[("name", active.scenario.name.clone())],
+                ),
+                Some(active) => locale.format(
+                    "ui-scenario-failed",
+                    &[("name", active.scenario.name.clone())],
+                ),
+                None => locale.format(
+                    "ui-game-over",
+                    &[("name", match_settings.player(event.player_won).name.clone())],
+                ),
+            };
         }
 
         for (ui_action, mut visibility) in game_over_container_visibility.iter_mut() {
@@ -1,11 +1,12 @@
 #[cfg(feature = "dev")]
 mod dev_tools;
 mod game;
+pub mod net;
 mod screen;
 mod ui;
 
 use bevy::{
-    asset::AssetMetaCheck,
+    asset::{io::AssetSourceBuilder, AssetMetaCheck},
     audio::{AudioPlugin, Volume},
     input::common_conditions::input_toggle_active,
     prelude::*,
@@ -28,6 +29,14 @@ impl Plugin for AppPlugin {
         // Spawn the main camera.
         app.add_systems(Startup, spawn_camera);
 
+        // Mods are read from their own folder next to `assets`. Sources have
+        // to be registered before `AssetPlugin` is added.
+        #[cfg(not(target_family = "wasm"))]
+        app.register_asset_source(
+            game::mods::MODS_SOURCE,
+            AssetSourceBuilder::platform_default(game::mods::MODS_DIR, None),
+        );
+
         // Add Bevy plugins.
         app.add_plugins(
             DefaultPlugins
@@ -62,7 +71,7 @@ impl Plugin for AppPlugin {
             });
 
         // Add other plugins.
-        app.add_plugins((game::plugin, screen::plugin, ui::plugin));
+        app.add_plugins((game::plugin, net::plugin, screen::plugin, ui::plugin));
 
         // Enable dev tools for dev builds.
         #[cfg(feature = "dev")]
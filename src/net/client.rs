@@ -0,0 +1,378 @@
+//! Client side of an online match.
+//!
+//! While a [`NetClient`] exists the local rule systems stand still: button
+//! presses and card clicks are forwarded to the server, and whatever the
+//! server reports is replayed through the regular la_mesa and game events.
+
+use std::net::TcpStream;
+use std::sync::{
+    mpsc::{channel, Receiver},
+    Mutex,
+};
+use std::thread;
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_la_mesa::{
+    events::{DrawHand, PlaceCardOnTable, RenderDeck},
+    Card, CardOnTable, Chip, ChipArea, LaMesaPluginSettings,
+};
+
+use super::protocol::{read_messages, send, ClientMessage, NetId, ServerMessage};
+use crate::{
+    game::{
+        cards::{
+            ChipType, DiscardChip, DropChip, GameOver, GameState, InformantReport, Kard, MoveChip,
+        },
+        checksum::StateDigest,
+        locale::Locale,
+        log::GameLog,
+    },
+    screen::Screen,
+};
+
+pub(crate) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            receive_server_messages,
+            track_spawned_cards,
+            track_spawned_chips,
+            verify_checksum,
+        )
+            .chain()
+            .run_if(in_state(Screen::Playing).and_then(resource_exists::<NetClient>)),
+    );
+}
+
+/// Frames to wait after a checksum before comparing against it.
+const CHECKSUM_SETTLE_FRAMES: usize = 3;
+
+/// Card announced by the server that hasn't been spawned on the table yet.
+struct PendingCard {
+    card: NetId,
+    kard: Kard,
+    /// Where to put the card once it exists; `None` for cards dealt to us.
+    place: Option<(usize, usize)>,
+}
+
+/// Connection to the game server, present during online matches only.
+#[derive(Resource)]
+pub struct NetClient {
+    stream: TcpStream,
+    messages: Mutex<Receiver<ServerMessage>>,
+    /// Messages put back to be handled by the next reader.
+    backlog: Vec<ServerMessage>,
+    /// Seat given by the server once the match starts.
+    pub player: Option<usize>,
+    hand_size: usize,
+    entities: HashMap<NetId, Entity>,
+    pending_cards: Vec<PendingCard>,
+    /// Chips announced by the server: id, player, type and area.
+    pending_chips: Vec<(NetId, usize, ChipType, usize)>,
+    draw_pending: bool,
+    /// Server digest to compare with before handling further messages.
+    awaiting_checksum: Option<StateDigest>,
+    checksum_frames: usize,
+    desynced: bool,
+}
+
+impl NetClient {
+    pub fn connect(address: &str, name: &str) -> std::io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        let reader = stream.try_clone()?;
+        let (sender, messages) = channel();
+        thread::spawn(move || {
+            read_messages(reader, |message| sender.send(message).is_ok());
+        });
+
+        send(
+            &stream,
+            &ClientMessage::Join {
+                name: name.to_string(),
+            },
+        )?;
+
+        Ok(Self {
+            stream,
+            messages: Mutex::new(messages),
+            backlog: vec![],
+            player: None,
+            hand_size: 0,
+            entities: HashMap::new(),
+            pending_cards: vec![],
+            pending_chips: vec![],
+            draw_pending: false,
+            awaiting_checksum: None,
+            checksum_frames: 0,
+            desynced: false,
+        })
+    }
+
+    pub fn send(&self, message: ClientMessage) {
+        if let Err(error) = send(&self.stream, &message) {
+            warn!("Could not reach the server: {}", error);
+        }
+    }
+
+    /// Messages received since the last call.
+    pub fn receive(&mut self) -> Vec<ServerMessage> {
+        let mut messages = std::mem::take(&mut self.backlog);
+        messages.extend(self.messages.lock().unwrap().try_iter());
+        messages
+    }
+
+    /// Hands messages back so that they are received again next time.
+    pub fn requeue(&mut self, messages: Vec<ServerMessage>) {
+        self.backlog = messages;
+    }
+
+    pub fn set_seat(&mut self, player: usize, hand_size: usize) {
+        self.player = Some(player);
+        self.hand_size = hand_size;
+    }
+
+    /// Whether the server waits for this client to act.
+    pub fn is_my_turn(&self, state: &GameState) -> bool {
+        self.player == Some(state.player)
+    }
+
+    /// Server id of a card or chip spawned for it.
+    pub fn net_id(&self, entity: Entity) -> Option<NetId> {
+        self.entities
+            .iter()
+            .find(|(_, e)| **e == entity)
+            .map(|(id, _)| *id)
+    }
+}
+
+fn receive_server_messages(
+    mut commands: Commands,
+    mut net: ResMut<NetClient>,
+    mut state: ResMut<GameState>,
+    mut log: ResMut<GameLog>,
+    mut ew_render_deck: EventWriter<RenderDeck<Kard>>,
+    mut ew_place_card_on_table: EventWriter<PlaceCardOnTable>,
+    mut ew_drop_chip: EventWriter<DropChip>,
+    mut ew_move_chip: EventWriter<MoveChip>,
+    mut ew_discard_chip: EventWriter<DiscardChip>,
+    mut ew_game_over: EventWriter<GameOver>,
+    mut ew_informant_report: EventWriter<InformantReport>,
+    locale: Res<Locale>,
+) {
+    // Nothing past a checksum is applied until the state has been checked.
+    if net.awaiting_checksum.is_some() {
+        return;
+    }
+
+    let mut messages = net.receive().into_iter();
+    while let Some(message) = messages.next() {
+        match message {
+            ServerMessage::Dealt { cards } => {
+                ew_render_deck.send(RenderDeck::<Kard> {
+                    marker: 1,
+                    deck: cards.iter().map(|(_, kard)| kard.clone()).collect(),
+                });
+                net.pending_cards
+                    .extend(cards.into_iter().map(|(card, kard)| PendingCard {
+                        card,
+                        kard,
+                        place: None,
+                    }));
+                net.draw_pending = true;
+            }
+            ServerMessage::CardPlaced {
+                card,
+                kard,
+                marker,
+                player,
+            } => {
+                if let Some(&card_entity) = net.entities.get(&card) {
+                    ew_place_card_on_table.send(PlaceCardOnTable {
+                        card_entity,
+                        marker,
+                        player,
+                    });
+                } else {
+                    // Cards we haven't seen are brought in from their deck.
+                    let deck_marker = if marker == 6 { 2 } else { 1 };
+                    ew_render_deck.send(RenderDeck::<Kard> {
+                        marker: deck_marker,
+                        deck: vec![kard.clone()],
+                    });
+                    net.pending_cards.push(PendingCard {
+                        card,
+                        kard,
+                        place: Some((marker, player)),
+                    });
+                }
+            }
+            ServerMessage::CardRemoved { card } => {
+                if let Some(entity) = net.entities.remove(&card) {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            ServerMessage::ChipDropped {
+                chip,
+                chip_type,
+                area,
+                player,
+            } => {
+                ew_drop_chip.send(DropChip {
+                    chip_type,
+                    area,
+                    player,
+                });
+                net.pending_chips.push((chip, player, chip_type, area));
+            }
+            ServerMessage::ChipMoved { chip, area, player } => {
+                if let Some(&entity) = net.entities.get(&chip) {
+                    ew_move_chip.send(MoveChip {
+                        entity,
+                        area,
+                        player,
+                    });
+                }
+            }
+            ServerMessage::ChipDiscarded { chip } => {
+                if let Some(entity) = net.entities.remove(&chip) {
+                    ew_discard_chip.send(DiscardChip { entity });
+                }
+            }
+            ServerMessage::State(snapshot) => snapshot.apply(&mut state),
+            ServerMessage::Log { turn_number, text } => log.push(turn_number, text),
+            ServerMessage::Informant { target, cards } => {
+                if let Some(player) = net.player {
+                    ew_informant_report.send(InformantReport {
+                        player,
+                        target,
+                        cards,
+                    });
+                }
+            }
+            ServerMessage::Rejected { reason } => {
+                let text = locale.format("log-server-rejected", &[("reason", reason)]);
+                log.push(state.turn_number, text);
+            }
+            ServerMessage::GameOver { player_won } => {
+                ew_game_over.send(GameOver { player_won });
+            }
+            ServerMessage::Checksum(digest) => {
+                net.awaiting_checksum = Some(digest);
+                net.checksum_frames = 0;
+                net.requeue(messages.collect());
+                return;
+            }
+            ServerMessage::Welcome { .. } | ServerMessage::Spectating { .. } => {}
+        }
+    }
+}
+
+/// Compares the mirrored state with the server's once everything announced
+/// before the checksum has been spawned and moved. Only the first divergence
+/// is reported, later ones usually follow from it.
+fn verify_checksum(
+    mut net: ResMut<NetClient>,
+    state: Res<GameState>,
+    chips: Query<(&Chip<ChipType>, &ChipArea)>,
+    cards_on_table: Query<(&Card<Kard>, &CardOnTable)>,
+    locale: Res<Locale>,
+    mut log: ResMut<GameLog>,
+) {
+    if net.awaiting_checksum.is_none() {
+        return;
+    }
+    // Events sent while receiving take a frame to be handled and another one
+    // for their commands to land.
+    net.checksum_frames += 1;
+    if net.checksum_frames < CHECKSUM_SETTLE_FRAMES
+        || !net.pending_cards.is_empty()
+        || !net.pending_chips.is_empty()
+    {
+        return;
+    }
+    let Some(mut expected) = net.awaiting_checksum.take() else {
+        return;
+    };
+
+    // The decks are hidden from clients.
+    expected.decks = None;
+    let local = StateDigest::new(&state, chips.iter(), cards_on_table.iter());
+    if net.desynced || local.checksum() == expected.checksum() {
+        return;
+    }
+    net.desynced = true;
+
+    // Server values come first in every difference.
+    let differences = expected.diff(&local).join("; ");
+    warn!("Desync at {}: {}", expected.describe(), differences);
+    let text = locale.format(
+        "log-desync",
+        &[("at", expected.describe()), ("differences", differences)],
+    );
+    log.push(state.turn_number, text);
+}
+
+/// Links cards spawned by la_mesa to the server ids they were rendered for.
+fn track_spawned_cards(
+    mut net: ResMut<NetClient>,
+    state: Res<GameState>,
+    cards: Query<(Entity, &Card<Kard>), Added<Card<Kard>>>,
+    plugin_settings: Res<LaMesaPluginSettings>,
+    mut ew_draw: EventWriter<DrawHand>,
+    mut ew_place_card_on_table: EventWriter<PlaceCardOnTable>,
+) {
+    for (entity, card) in &cards {
+        let Some(index) = net.pending_cards.iter().position(|pending| {
+            pending.kard.card_type == card.data.card_type
+                && pending.kard.filename == card.data.filename
+        }) else {
+            continue;
+        };
+        let pending = net.pending_cards.remove(index);
+        net.entities.insert(pending.card, entity);
+
+        if let Some((marker, player)) = pending.place {
+            ew_place_card_on_table.send(PlaceCardOnTable {
+                card_entity: entity,
+                marker,
+                player,
+            });
+        }
+    }
+
+    // The deck only holds what the server dealt, so drawing takes all of it.
+    let dealt_pending = net
+        .pending_cards
+        .iter()
+        .any(|pending| pending.place.is_none());
+    if net.draw_pending && !dealt_pending {
+        net.draw_pending = false;
+        ew_draw.send(DrawHand {
+            deck_marker: 1,
+            num_cards: net.hand_size.max(plugin_settings.hand_size),
+            player: net.player.unwrap_or(state.player),
+        });
+    }
+}
+
+/// Links chips spawned by `handle_drop_chip` to the server ids they were
+/// dropped for. They land straight in the announced area, which is further
+/// along than production for spectators catching up.
+fn track_spawned_chips(
+    mut net: ResMut<NetClient>,
+    chips: Query<(Entity, &Chip<ChipType>, &ChipArea), Added<Chip<ChipType>>>,
+) {
+    for (entity, chip, area) in &chips {
+        let Some(index) =
+            net.pending_chips
+                .iter()
+                .position(|(_, player, chip_type, target_area)| {
+                    *player == area.player && *chip_type == chip.data && *target_area == area.marker
+                })
+        else {
+            continue;
+        };
+        let (id, _, _, _) = net.pending_chips.remove(index);
+        net.entities.insert(id, entity);
+    }
+}
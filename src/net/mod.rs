@@ -0,0 +1,16 @@
+//! Online play: a headless server owning the match and clients that mirror it.
+
+use bevy::prelude::*;
+
+pub mod client;
+pub mod protocol;
+pub mod server;
+
+pub use crate::game::settings::{MAX_PLAYERS, MIN_PLAYERS};
+
+/// Used by the server binary and the lobby when nothing else is given.
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(client::plugin);
+}
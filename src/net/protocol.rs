@@ -0,0 +1,177 @@
+//! Messages exchanged between the server and its clients.
+//!
+//! Every message is a single line of RON. Cards and chips are referred to by
+//! the bits of the server entity that represents them, so clients keep a map
+//! from those ids to their own entities.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::TcpStream;
+
+use bevy::log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::game::{
+    cards::{
+        CardType, Cargo, ChipType, Effect, GameState, Kard, Market, PendingPayment, TurnPhase,
+    },
+    checksum::StateDigest,
+};
+
+/// Id of a card or chip, shared by the server and every client.
+pub type NetId = u64;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ClientMessage {
+    Join { name: String },
+    DrawHand,
+    PlayCard { card: NetId },
+    AdvancePhase,
+    Borrow,
+    Repay,
+    CycleCargo,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ServerMessage {
+    /// Sent once every seat is taken; `player` is the seat of the receiver.
+    Welcome {
+        player: usize,
+        names: Vec<String>,
+        hand_size: usize,
+        seed: u64,
+    },
+    /// Sent to spectators instead of [`ServerMessage::Welcome`].
+    Spectating {
+        names: Vec<String>,
+    },
+    /// Cards dealt to the receiver. Nobody else learns about them.
+    Dealt {
+        cards: Vec<(NetId, Kard)>,
+    },
+    CardPlaced {
+        card: NetId,
+        kard: Kard,
+        marker: usize,
+        player: usize,
+    },
+    CardRemoved {
+        card: NetId,
+    },
+    ChipDropped {
+        chip: NetId,
+        chip_type: ChipType,
+        area: usize,
+        player: usize,
+    },
+    ChipMoved {
+        chip: NetId,
+        area: usize,
+        player: usize,
+    },
+    ChipDiscarded {
+        chip: NetId,
+    },
+    State(GameSnapshot),
+    /// Rules state after a phase advance, for the clients to check theirs
+    /// against.
+    Checksum(StateDigest),
+    /// A new line of the server's game log.
+    Log {
+        turn_number: usize,
+        text: String,
+    },
+    /// Hand of `target` as reported by an Informant, sent to its player only.
+    Informant {
+        target: usize,
+        cards: Vec<CardType>,
+    },
+    Rejected {
+        reason: String,
+    },
+    GameOver {
+        player_won: usize,
+    },
+}
+
+/// The parts of [`GameState`] the clients display.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameSnapshot {
+    pub turn_number: usize,
+    pub phase: TurnPhase,
+    pub player: usize,
+    pub bank: Vec<i32>,
+    pub debt: Vec<i32>,
+    pub heat: Vec<i32>,
+    pub cargo: Vec<Cargo>,
+    pub storage_capacity: Vec<[usize; 2]>,
+    pub effects: Vec<Effect>,
+    pub market_sales: Vec<(Market, ChipType, usize)>,
+    pub pending_payments: Vec<PendingPayment>,
+}
+
+impl GameSnapshot {
+    pub fn new(state: &GameState) -> Self {
+        Self {
+            turn_number: state.turn_number,
+            phase: state.phase,
+            player: state.player,
+            bank: state.bank.clone(),
+            debt: state.debt.clone(),
+            heat: state.heat.clone(),
+            cargo: state.cargo.clone(),
+            storage_capacity: state.storage_capacity.clone(),
+            effects: state.effects.clone(),
+            market_sales: state
+                .market_sales
+                .iter()
+                .map(|(&(market, chip_type), &sold)| (market, chip_type, sold))
+                .collect(),
+            pending_payments: state.pending_payments.clone(),
+        }
+    }
+
+    pub fn apply(&self, state: &mut GameState) {
+        state.turn_number = self.turn_number;
+        state.phase = self.phase;
+        state.player = self.player;
+        state.bank.clone_from(&self.bank);
+        state.debt.clone_from(&self.debt);
+        state.heat.clone_from(&self.heat);
+        state.cargo.clone_from(&self.cargo);
+        state.storage_capacity.clone_from(&self.storage_capacity);
+        state.effects.clone_from(&self.effects);
+        state.market_sales = self
+            .market_sales
+            .iter()
+            .map(|&(market, chip_type, sold)| ((market, chip_type), sold))
+            .collect();
+        state.pending_payments.clone_from(&self.pending_payments);
+    }
+}
+
+pub fn send<T: Serialize>(mut stream: &TcpStream, message: &T) -> io::Result<()> {
+    let line = ron::to_string(message).map_err(|error| io::Error::other(error.to_string()))?;
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\n")
+}
+
+/// Reads messages from `stream` until it closes, handing each to `on_message`.
+/// Returns early when `on_message` returns `false`.
+pub fn read_messages<T: DeserializeOwned>(
+    stream: TcpStream,
+    mut on_message: impl FnMut(T) -> bool,
+) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        match ron::from_str(&line) {
+            Ok(message) => {
+                if !on_message(message) {
+                    return;
+                }
+            }
+            Err(error) => warn!("Invalid message {:?}: {}", line, error),
+        }
+    }
+}
@@ -0,0 +1,669 @@
+//! Headless game server owning the authoritative match.
+//!
+//! The server runs the same rule systems as a local game on plain entities
+//! without any rendering. Actions received from clients are validated and
+//! turned into the events the UI would send; everything the rules do in
+//! response is broadcast back so that clients replay it with the la_mesa
+//! visuals.
+//!
+//! Connections beyond the seats become spectators. They receive everything
+//! but the dealt hands, optionally with a delay so that they can't help a
+//! player by telling them what happens at the other seats.
+
+use std::collections::VecDeque;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{
+    mpsc::{channel, Receiver, Sender},
+    Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy_la_mesa::{
+    events::{
+        AlignCardsInHand, AlignChipsOnTable, CardPress, DeckShuffle, DrawHand, PlaceCardOffTable,
+        PlaceCardOnTable,
+    },
+    Card, CardOnTable, Chip, ChipArea, Deck, Hand,
+};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use super::protocol::{read_messages, send, ClientMessage, GameSnapshot, NetId, ServerMessage};
+use crate::game::{
+    self,
+    cards::{
+        apply_card_effects, handle_next_phase, load_event_deck, load_playing_deck, AdvancePhase,
+        ChipType, DiscardChip, DropChip, GameOver, GameState, InformantReport, Kard, MoveChip,
+        TurnPhase,
+    },
+    checksum::StateDigest,
+    log::GameLog,
+    settings::{GameRng, MatchSettings, RulesPreset, MAX_PLAYERS, MIN_PLAYERS},
+    spawn::ui::handle_card_press,
+};
+
+pub struct ServerPlugin {
+    pub address: String,
+    pub num_players: usize,
+    /// How late spectators see what happens.
+    pub spectator_delay: Duration,
+}
+
+impl Plugin for ServerPlugin {
+    fn build(&self, app: &mut App) {
+        game::rules_plugin(app);
+
+        // Events normally owned by la_mesa, handled here without visuals.
+        app.add_event::<DrawHand>()
+            .add_event::<DeckShuffle>()
+            .add_event::<CardPress>()
+            .add_event::<PlaceCardOnTable>()
+            .add_event::<PlaceCardOffTable>()
+            .add_event::<AlignCardsInHand>()
+            .add_event::<AlignChipsOnTable<ChipType>>();
+
+        match Connections::listen(&self.address, self.num_players, self.spectator_delay) {
+            Ok(connections) => app.insert_resource(connections),
+            Err(error) => {
+                error!("Could not listen on {}: {}", self.address, error);
+                app.add_systems(Startup, |mut ew_exit: EventWriter<AppExit>| {
+                    ew_exit.send(AppExit::error());
+                });
+                return;
+            }
+        };
+        app.add_systems(
+            Update,
+            (
+                accept_players,
+                seat_spectators,
+                receive_actions,
+                handle_card_press,
+                deal_cards,
+                place_cards_on_table,
+                place_cards_off_table,
+                drop_chips,
+                move_chips,
+                discard_chips,
+                broadcast_state,
+                broadcast_log,
+                send_informant_reports,
+                broadcast_game_over,
+            )
+                .chain()
+                // Whatever the rules send this frame is broadcast this frame.
+                .after(apply_card_effects)
+                .after(handle_next_phase),
+        );
+        // Checksums describe the state once this frame's changes are applied.
+        app.add_systems(Last, (broadcast_checksums, flush_spectators).chain());
+    }
+}
+
+#[derive(Resource)]
+struct Connections {
+    num_players: usize,
+    /// Stream of each seated player, in seat order.
+    streams: Vec<TcpStream>,
+    /// Names sent by the players; `None` until their join message arrives.
+    names: Vec<Option<String>>,
+    started: bool,
+    /// Spectators who connected before the match started.
+    waiting_spectators: Vec<TcpStream>,
+    spectators: Mutex<Vec<Spectator>>,
+    spectator_delay: Duration,
+    accepted: Mutex<Receiver<TcpStream>>,
+    incoming: Mutex<Receiver<(usize, ClientMessage)>>,
+    incoming_sender: Sender<(usize, ClientMessage)>,
+}
+
+impl Connections {
+    fn listen(
+        address: &str,
+        num_players: usize,
+        spectator_delay: Duration,
+    ) -> std::io::Result<Self> {
+        // Matches the seats `MatchSettings` has room for.
+        let num_players = num_players.clamp(MIN_PLAYERS, MAX_PLAYERS);
+        let listener = TcpListener::bind(address)?;
+        info!("Waiting for {} players on {}", num_players, address);
+
+        let (accepted_sender, accepted) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if accepted_sender.send(stream).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let (incoming_sender, incoming) = channel();
+        Ok(Self {
+            num_players,
+            streams: vec![],
+            names: vec![],
+            started: false,
+            waiting_spectators: vec![],
+            spectators: Mutex::new(vec![]),
+            spectator_delay,
+            accepted: Mutex::new(accepted),
+            incoming: Mutex::new(incoming),
+            incoming_sender,
+        })
+    }
+
+    fn send_to(&self, player: usize, message: &ServerMessage) {
+        if let Some(stream) = self.streams.get(player - 1) {
+            if let Err(error) = send(stream, message) {
+                warn!("Could not reach player {}: {}", player, error);
+            }
+        }
+    }
+
+    fn broadcast(&self, message: &ServerMessage) {
+        for player in 1..=self.streams.len() {
+            self.send_to(player, message);
+        }
+        for spectator in self.spectators.lock().unwrap().iter_mut() {
+            spectator.queue(message.clone(), self.spectator_delay);
+        }
+    }
+}
+
+struct Spectator {
+    stream: TcpStream,
+    /// Messages waiting for their time to be sent.
+    queue: VecDeque<(Instant, ServerMessage)>,
+}
+
+impl Spectator {
+    fn queue(&mut self, message: ServerMessage, delay: Duration) {
+        self.queue.push_back((Instant::now() + delay, message));
+    }
+}
+
+fn net_id(entity: Entity) -> NetId {
+    entity.to_bits()
+}
+
+fn accept_players(
+    mut commands: Commands,
+    mut connections: ResMut<Connections>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut game_state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
+) {
+    let accepted = connections
+        .accepted
+        .lock()
+        .unwrap()
+        .try_iter()
+        .collect::<Vec<_>>();
+    for stream in accepted {
+        if connections.started || connections.streams.len() == connections.num_players {
+            info!("A spectator joined");
+            connections.waiting_spectators.push(stream);
+            continue;
+        }
+
+        let player = connections.streams.len() + 1;
+        let Ok(reader) = stream.try_clone() else {
+            continue;
+        };
+        let sender = connections.incoming_sender.clone();
+        thread::spawn(move || {
+            read_messages(reader, |message| sender.send((player, message)).is_ok());
+        });
+
+        info!("Player {} joined", player);
+        connections.streams.push(stream);
+        connections.names.push(None);
+    }
+
+    if connections.started
+        || connections.names.len() < connections.num_players
+        || connections.names.iter().any(Option::is_none)
+    {
+        return;
+    }
+    connections.started = true;
+
+    // Every seat is taken: set up the match exactly like the setup screen.
+    let seed = rand::random::<u32>() as u64;
+    match_settings.set_num_players(connections.num_players);
+    match_settings.preset = RulesPreset::Standard;
+    match_settings.seed = seed;
+    let names = connections
+        .names
+        .iter()
+        .flatten()
+        .cloned()
+        .collect::<Vec<_>>();
+    for (player, name) in match_settings.players.iter_mut().zip(&names) {
+        player.name.clone_from(name);
+    }
+    *game_state = GameState::from_settings(&match_settings);
+    rng.0 = StdRng::seed_from_u64(seed);
+
+    for (marker, mut deck) in [
+        (1, load_playing_deck(connections.num_players)),
+        (2, load_event_deck(connections.num_players)),
+    ] {
+        deck.shuffle(&mut rng.0);
+        for (index, kard) in deck.into_iter().enumerate() {
+            commands.spawn((
+                Card::<Kard> {
+                    data: kard,
+                    transform: None,
+                },
+                Deck { marker },
+                // The top of a deck is the card with the highest z.
+                Transform::from_xyz(0.0, 0.0, index as f32),
+            ));
+        }
+    }
+
+    for player in 1..=connections.num_players {
+        connections.send_to(
+            player,
+            &ServerMessage::Welcome {
+                player,
+                names: names.clone(),
+                hand_size: match_settings.preset.hand_size(),
+                seed,
+            },
+        );
+    }
+    connections.broadcast(&ServerMessage::State(GameSnapshot::new(&game_state)));
+}
+
+/// Brings spectators up to date with the match and adds them to the
+/// broadcasts. Catching up goes through the same delay as the rest.
+fn seat_spectators(
+    mut connections: ResMut<Connections>,
+    match_settings: Res<MatchSettings>,
+    game_state: Res<GameState>,
+    cards_on_table: Query<(Entity, &Card<Kard>, &CardOnTable)>,
+    chips: Query<(Entity, &Chip<ChipType>, &ChipArea)>,
+) {
+    if !connections.started || connections.waiting_spectators.is_empty() {
+        return;
+    }
+
+    let mut catch_up = vec![
+        ServerMessage::Spectating {
+            names: match_settings
+                .players
+                .iter()
+                .map(|player| player.name.clone())
+                .collect(),
+        },
+        ServerMessage::State(GameSnapshot::new(&game_state)),
+    ];
+    catch_up.extend(cards_on_table.iter().map(|(entity, card, card_on_table)| {
+        ServerMessage::CardPlaced {
+            card: net_id(entity),
+            kard: card.data.clone(),
+            marker: card_on_table.marker,
+            player: card_on_table.player,
+        }
+    }));
+    catch_up.extend(
+        chips
+            .iter()
+            .map(|(entity, chip, area)| ServerMessage::ChipDropped {
+                chip: net_id(entity),
+                chip_type: chip.data,
+                area: area.marker,
+                player: area.player,
+            }),
+    );
+
+    let delay = connections.spectator_delay;
+    let waiting = std::mem::take(&mut connections.waiting_spectators);
+    let mut spectators = connections.spectators.lock().unwrap();
+    for stream in waiting {
+        let mut spectator = Spectator {
+            stream,
+            queue: VecDeque::new(),
+        };
+        for message in catch_up.iter() {
+            spectator.queue(message.clone(), delay);
+        }
+        spectators.push(spectator);
+    }
+}
+
+fn receive_actions(
+    mut connections: ResMut<Connections>,
+    mut game_state: ResMut<GameState>,
+    match_settings: Res<MatchSettings>,
+    cards_in_hand: Query<&Hand, With<Card<Kard>>>,
+    mut ew_draw: EventWriter<DrawHand>,
+    mut ew_card_press: EventWriter<CardPress>,
+    mut ew_advance_phase: EventWriter<AdvancePhase>,
+) {
+    let messages = connections
+        .incoming
+        .lock()
+        .unwrap()
+        .try_iter()
+        .collect::<Vec<_>>();
+    for (player, message) in messages {
+        if let ClientMessage::Join { name } = message {
+            if !connections.started {
+                connections.names[player - 1] = Some(name);
+            }
+            continue;
+        }
+
+        if !connections.started || player != game_state.player {
+            connections.send_to(
+                player,
+                &ServerMessage::Rejected {
+                    reason: "It's not your turn".to_string(),
+                },
+            );
+            continue;
+        }
+
+        let phase = game_state.phase;
+        let accepted = match message {
+            ClientMessage::DrawHand if phase == TurnPhase::Prepare => {
+                ew_draw.send(DrawHand {
+                    deck_marker: 1,
+                    num_cards: match_settings.preset.hand_size(),
+                    player,
+                });
+                ew_advance_phase.send(AdvancePhase);
+                true
+            }
+            ClientMessage::PlayCard { card } if phase == TurnPhase::PlaceCardsOnTable => {
+                // Ids come from the client and may be malformed.
+                let card_entity = Entity::try_from_bits(card).ok().filter(|&card_entity| {
+                    cards_in_hand
+                        .get(card_entity)
+                        .is_ok_and(|hand| hand.player == player)
+                });
+                if let Some(card_entity) = card_entity {
+                    ew_card_press.send(CardPress { card_entity });
+                }
+                card_entity.is_some()
+            }
+            ClientMessage::AdvancePhase if phase == TurnPhase::PlaceCardsOnTable => {
+                ew_advance_phase.send(AdvancePhase);
+                true
+            }
+            ClientMessage::Borrow if phase == TurnPhase::Prepare => game_state.borrow(player),
+            ClientMessage::Repay if phase == TurnPhase::Prepare => game_state.repay(player) > 0,
+            ClientMessage::CycleCargo => {
+                game_state.cycle_cargo(player);
+                true
+            }
+            _ => false,
+        };
+
+        if !accepted {
+            connections.send_to(
+                player,
+                &ServerMessage::Rejected {
+                    reason: format!("{:?} isn't allowed now", message),
+                },
+            );
+        }
+    }
+}
+
+/// Tops up the hand from the deck; only the owner learns the new cards.
+fn deal_cards(
+    mut commands: Commands,
+    mut er_draw: EventReader<DrawHand>,
+    connections: Res<Connections>,
+    decks: Query<(Entity, &Card<Kard>, &Deck, &Transform)>,
+    hands: Query<&Hand, With<Card<Kard>>>,
+) {
+    for draw in er_draw.read() {
+        let in_hand = hands
+            .iter()
+            .filter(|hand| hand.player == draw.player)
+            .count();
+        let mut deck = decks
+            .iter()
+            .filter(|(_, _, deck, _)| deck.marker == draw.deck_marker)
+            .collect::<Vec<_>>();
+        deck.sort_by(|(_, _, _, t1), (_, _, _, t2)| {
+            t2.translation.z.partial_cmp(&t1.translation.z).unwrap()
+        });
+
+        let dealt = deck
+            .into_iter()
+            .take(draw.num_cards.saturating_sub(in_hand))
+            .map(|(entity, card, _, _)| {
+                commands.entity(entity).remove::<Deck>().insert(Hand {
+                    player: draw.player,
+                });
+                (net_id(entity), card.data.clone())
+            })
+            .collect::<Vec<_>>();
+
+        connections.send_to(draw.player, &ServerMessage::Dealt { cards: dealt });
+    }
+}
+
+fn place_cards_on_table(
+    mut commands: Commands,
+    mut er_place_card_on_table: EventReader<PlaceCardOnTable>,
+    connections: Res<Connections>,
+    cards: Query<&Card<Kard>>,
+) {
+    for event in er_place_card_on_table.read() {
+        let Ok(card) = cards.get(event.card_entity) else {
+            continue;
+        };
+        commands
+            .entity(event.card_entity)
+            .remove::<Hand>()
+            .remove::<Deck>()
+            .insert(CardOnTable {
+                marker: event.marker,
+                player: event.player,
+            });
+        connections.broadcast(&ServerMessage::CardPlaced {
+            card: net_id(event.card_entity),
+            kard: card.data.clone(),
+            marker: event.marker,
+            player: event.player,
+        });
+    }
+}
+
+/// Played cards go back under their deck.
+fn place_cards_off_table(
+    mut commands: Commands,
+    mut er_place_card_off_table: EventReader<PlaceCardOffTable>,
+    connections: Res<Connections>,
+    decks: Query<(&Deck, &Transform)>,
+) {
+    for event in er_place_card_off_table.read() {
+        let bottom = decks
+            .iter()
+            .filter(|(deck, _)| deck.marker == event.deck_marker)
+            .map(|(_, transform)| transform.translation.z)
+            .fold(0.0, f32::min);
+        commands
+            .entity(event.card_entity)
+            .remove::<CardOnTable>()
+            .remove::<Hand>()
+            .insert((
+                Deck {
+                    marker: event.deck_marker,
+                },
+                Transform::from_xyz(0.0, 0.0, bottom - 1.0),
+            ));
+        connections.broadcast(&ServerMessage::CardRemoved {
+            card: net_id(event.card_entity),
+        });
+    }
+}
+
+fn drop_chips(
+    mut commands: Commands,
+    mut er_drop_chip: EventReader<DropChip>,
+    connections: Res<Connections>,
+    game_state: Res<GameState>,
+    chips: Query<&ChipArea, With<Chip<ChipType>>>,
+) {
+    let mut dropped = vec![0; game_state.num_players()];
+    for drop_chip in er_drop_chip.read() {
+        // Same warehouse limit as on the board.
+        let chips_in_area = chips
+            .iter()
+            .filter(|area| area.player == drop_chip.player && area.marker == drop_chip.area)
+            .count();
+        if !game_state.has_room(
+            drop_chip.player,
+            drop_chip.area,
+            chips_in_area + dropped[drop_chip.player - 1],
+        ) {
+            continue;
+        }
+        dropped[drop_chip.player - 1] += 1;
+
+        let chip = commands
+            .spawn((
+                Chip::<ChipType> {
+                    data: drop_chip.chip_type,
+                    turn_activation_1: game_state.turn_number,
+                    turn_activation_2: 0,
+                },
+                ChipArea {
+                    marker: drop_chip.area,
+                    player: drop_chip.player,
+                },
+                Transform::default(),
+            ))
+            .id();
+        connections.broadcast(&ServerMessage::ChipDropped {
+            chip: net_id(chip),
+            chip_type: drop_chip.chip_type,
+            area: drop_chip.area,
+            player: drop_chip.player,
+        });
+    }
+}
+
+fn move_chips(
+    mut commands: Commands,
+    mut er_move_chip: EventReader<MoveChip>,
+    connections: Res<Connections>,
+) {
+    for move_chip in er_move_chip.read() {
+        let Some(mut entity) = commands.get_entity(move_chip.entity) else {
+            continue;
+        };
+        entity.insert(ChipArea {
+            marker: move_chip.area,
+            player: move_chip.player,
+        });
+        connections.broadcast(&ServerMessage::ChipMoved {
+            chip: net_id(move_chip.entity),
+            area: move_chip.area,
+            player: move_chip.player,
+        });
+    }
+}
+
+fn discard_chips(
+    mut commands: Commands,
+    mut er_discard_chip: EventReader<DiscardChip>,
+    connections: Res<Connections>,
+) {
+    for discard_chip in er_discard_chip.read() {
+        let Some(mut entity) = commands.get_entity(discard_chip.entity) else {
+            continue;
+        };
+        entity.despawn();
+        connections.broadcast(&ServerMessage::ChipDiscarded {
+            chip: net_id(discard_chip.entity),
+        });
+    }
+}
+
+fn broadcast_state(connections: Res<Connections>, game_state: Res<GameState>) {
+    if connections.started && game_state.is_changed() {
+        connections.broadcast(&ServerMessage::State(GameSnapshot::new(&game_state)));
+    }
+}
+
+fn broadcast_log(connections: Res<Connections>, log: Res<GameLog>, mut sent: Local<usize>) {
+    for entry in log.entries.iter().skip(*sent) {
+        connections.broadcast(&ServerMessage::Log {
+            turn_number: entry.turn_number,
+            text: entry.text.clone(),
+        });
+    }
+    *sent = log.entries.len();
+}
+
+/// Sends a digest once per phase. `AdvancePhase` can't tell: automatic
+/// phases send it every frame until the phase timer lets one through.
+fn broadcast_checksums(
+    connections: Res<Connections>,
+    mut checked_at: Local<Option<(usize, usize, TurnPhase)>>,
+    game_state: Res<GameState>,
+    chips: Query<(&Chip<ChipType>, &ChipArea)>,
+    cards_on_table: Query<(&Card<Kard>, &CardOnTable)>,
+    cards_in_deck: Query<(&Card<Kard>, &Deck, &Transform)>,
+) {
+    let now = (game_state.turn_number, game_state.player, game_state.phase);
+    if !connections.started || *checked_at == Some(now) {
+        return;
+    }
+    *checked_at = Some(now);
+    let digest = StateDigest::new(&game_state, chips.iter(), cards_on_table.iter())
+        .with_decks(cards_in_deck.iter());
+    connections.broadcast(&ServerMessage::Checksum(digest));
+}
+
+/// Sends spectators what is due and drops the ones that left.
+fn flush_spectators(connections: Res<Connections>) {
+    let now = Instant::now();
+    connections
+        .spectators
+        .lock()
+        .unwrap()
+        .retain_mut(|spectator| {
+            while spectator.queue.front().is_some_and(|(due, _)| *due <= now) {
+                let (_, message) = spectator.queue.pop_front().unwrap();
+                if send(&spectator.stream, &message).is_err() {
+                    info!("A spectator left");
+                    return false;
+                }
+            }
+            true
+        });
+}
+
+fn send_informant_reports(
+    connections: Res<Connections>,
+    mut er_informant_report: EventReader<InformantReport>,
+) {
+    for report in er_informant_report.read() {
+        connections.send_to(
+            report.player,
+            &ServerMessage::Informant {
+                target: report.target,
+                cards: report.cards.clone(),
+            },
+        );
+    }
+}
+
+fn broadcast_game_over(connections: Res<Connections>, mut er_game_over: EventReader<GameOver>) {
+    for game_over in er_game_over.read() {
+        connections.broadcast(&ServerMessage::GameOver {
+            player_won: game_over.player_won,
+        });
+    }
+}
@@ -0,0 +1,154 @@
+//! The campaign screen listing the tutorial and the scenarios unlocked so far.
+
+use bevy::prelude::*;
+use bevy_la_mesa::LaMesaPluginSettings;
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        campaign::{ActiveScenario, Campaign, CampaignHandle, CampaignProgress, Scenario},
+        cards::GameState,
+        locale::{Locale, Localized},
+        settings::{GameRng, MatchSettings},
+        spectator::Spectator,
+        tutorial::Tutorial,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Campaign), enter_campaign);
+    app.add_systems(OnExit(Screen::Campaign), exit_campaign);
+
+    app.register_type::<CampaignAction>();
+    app.add_systems(
+        Update,
+        handle_campaign_action.run_if(in_state(Screen::Campaign)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum CampaignAction {
+    Tutorial,
+    Play(usize),
+    Back,
+}
+
+fn enter_campaign(
+    mut commands: Commands,
+    campaign_handle: Res<CampaignHandle>,
+    campaigns: Res<Assets<Campaign>>,
+    scenarios: Res<Assets<Scenario>>,
+    progress: Res<CampaignProgress>,
+    locale: Res<Locale>,
+) {
+    let campaign_scenarios = campaigns
+        .get(&campaign_handle.campaign)
+        .map(|campaign| {
+            campaign
+                .scenarios
+                .iter()
+                .filter_map(|handle| scenarios.get(handle))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Campaign))
+        .with_children(|children| {
+            children
+                .header(locale.get("campaign-header"))
+                .insert(Localized("campaign-header"));
+            children
+                .button(locale.get("campaign-tutorial"))
+                .insert((CampaignAction::Tutorial, Localized("campaign-tutorial")));
+
+            for (index, scenario) in campaign_scenarios.iter().enumerate() {
+                let number = ("number", (index + 1).to_string());
+                if progress.is_unlocked(index) {
+                    children
+                        .button(locale.format(
+                            "campaign-scenario",
+                            &[number, ("name", scenario.name.clone())],
+                        ))
+                        .insert(CampaignAction::Play(index));
+                    children.label(format!(
+                        "{} {}",
+                        scenario.briefing,
+                        scenario.objective.describe(&locale)
+                    ));
+                } else {
+                    children.label(locale.format("campaign-locked", &[number]));
+                }
+            }
+
+            children
+                .button(locale.get("common-back"))
+                .insert((CampaignAction::Back, Localized("common-back")));
+        });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
+}
+
+fn exit_campaign(mut commands: Commands) {
+    commands.trigger(PlaySoundtrack::Disable);
+}
+
+fn handle_campaign_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&CampaignAction>,
+    campaign_handle: Res<CampaignHandle>,
+    campaigns: Res<Assets<Campaign>>,
+    scenarios: Res<Assets<Scenario>>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut plugin_settings: ResMut<LaMesaPluginSettings>,
+    mut game_state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        let (index, handle) = match *action {
+            CampaignAction::Tutorial => (None, Some(&campaign_handle.tutorial)),
+            CampaignAction::Play(index) => (
+                Some(index),
+                campaigns
+                    .get(&campaign_handle.campaign)
+                    .and_then(|campaign| campaign.scenarios.get(index)),
+            ),
+            CampaignAction::Back => {
+                next_screen.set(Screen::Title);
+                continue;
+            }
+        };
+        let Some(scenario) = handle.and_then(|handle| scenarios.get(handle)) else {
+            continue;
+        };
+
+        *match_settings = scenario.match_settings(rand::random::<u32>() as u64);
+        plugin_settings.num_players = match_settings.num_players();
+        plugin_settings.hand_size = match_settings.preset.hand_size();
+        *game_state = scenario.game_state(&match_settings);
+        rng.0 = StdRng::seed_from_u64(match_settings.seed);
+        commands.insert_resource(ActiveScenario {
+            index,
+            scenario: scenario.clone(),
+            finished: false,
+        });
+        commands.remove_resource::<Spectator>();
+        if *action == CampaignAction::Tutorial {
+            commands.insert_resource(Tutorial::default());
+        } else {
+            commands.remove_resource::<Tutorial>();
+        }
+        next_screen.set(Screen::Playing);
+    }
+}
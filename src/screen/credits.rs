@@ -4,7 +4,11 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack},
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        locale::{Locale, Localized},
+    },
     ui::prelude::*,
 };
 
@@ -25,20 +29,29 @@ enum CreditsAction {
     Back,
 }
 
-fn enter_credits(mut commands: Commands) {
+fn enter_credits(mut commands: Commands, locale: Res<Locale>) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Credits))
         .with_children(|children| {
-            children.header("Made by");
-            children.label("Programming:  cwiz");
-            children.label("Idea: GPT-4o");
-            children.label("Ruleset: GPT-4o");
+            children
+                .header(locale.get("credits-made-by"))
+                .insert(Localized("credits-made-by"));
+            let name = |name: &str| [("name", name.to_string())];
+            children.label(locale.format("credits-programming", &name("cwiz")));
+            children.label(locale.format("credits-idea", &name("GPT-4o")));
+            children.label(locale.format("credits-ruleset", &name("GPT-4o")));
 
-            children.header("Assets");
-            children.label("Neural Networks");
+            children
+                .header(locale.get("credits-assets"))
+                .insert(Localized("credits-assets"));
+            children
+                .label(locale.get("credits-neural-networks"))
+                .insert(Localized("credits-neural-networks"));
 
-            children.button("Back").insert(CreditsAction::Back);
+            children
+                .button(locale.get("common-back"))
+                .insert((CreditsAction::Back, Localized("common-back")));
         });
 
     commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Credits));
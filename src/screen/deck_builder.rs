@@ -0,0 +1,322 @@
+//! The deck builder screen where a seat assembles its deck from the card pool.
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        deck_builder::{DeckList, CARD_POOL, DECK_BUDGET, DECK_NAME_MAX_LENGTH},
+        locale::{Locale, Localized},
+        settings::MatchSettings,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::DeckBuilder), enter_deck_builder);
+    app.add_systems(OnExit(Screen::DeckBuilder), exit_deck_builder);
+
+    app.register_type::<DeckBuilderAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_deck_builder_action,
+            handle_name_input,
+            update_deck_builder_labels,
+        )
+            .chain()
+            .run_if(in_state(Screen::DeckBuilder)),
+    );
+}
+
+/// Saved decks offered for loading, the rest is reachable by name only.
+const MAX_SAVED_DECKS_SHOWN: usize = 6;
+
+/// Deck being edited for a seat, inserted before entering the screen.
+#[derive(Resource)]
+pub struct DeckBuilder {
+    pub seat: usize,
+    pub deck: DeckList,
+    saved: Vec<DeckList>,
+    editing_name: bool,
+    message: String,
+}
+
+impl DeckBuilder {
+    pub fn new(seat: usize, deck: Option<DeckList>) -> Self {
+        Self {
+            seat,
+            deck: deck.unwrap_or_else(DeckList::standard),
+            saved: DeckList::load_all(),
+            editing_name: false,
+            message: String::new(),
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum DeckBuilderAction {
+    EditName,
+    Remove(usize),
+    Add(usize),
+    LabelCopies(usize),
+    LabelSummary,
+    LabelProblems,
+    Load(usize),
+    Save,
+    UseStandard,
+    Confirm,
+    Back,
+}
+
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn enter_deck_builder(
+    mut commands: Commands,
+    builder: Res<DeckBuilder>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::DeckBuilder))
+        .with_children(|children| {
+            children.header(locale.format(
+                "deck-header",
+                &[("name", match_settings.player(builder.seat).name.clone())],
+            ));
+            children
+                .button(locale.format("deck-name", &[("name", builder.deck.name.clone())]))
+                .insert(DeckBuilderAction::EditName);
+
+            for (index, card) in CARD_POOL.iter().enumerate() {
+                children
+                    .spawn((Name::new(format!("Card Row {}", index)), row()))
+                    .with_children(|children| {
+                        children.label(locale.format(
+                            "deck-card",
+                            &[
+                                ("card", locale.card_name(card.card_type)),
+                                ("cost", card.cost.to_string()),
+                                ("min", card.min_copies.to_string()),
+                                ("max", card.max_copies.to_string()),
+                            ],
+                        ));
+                        children.label(locale.card_rules(card.card_type));
+                        children
+                            .button("-")
+                            .insert(DeckBuilderAction::Remove(index));
+                        children
+                            .label("0")
+                            .insert(DeckBuilderAction::LabelCopies(index));
+                        children.button("+").insert(DeckBuilderAction::Add(index));
+                    });
+            }
+
+            children.label("").insert(DeckBuilderAction::LabelSummary);
+            children.label("").insert(DeckBuilderAction::LabelProblems);
+
+            if !builder.saved.is_empty() {
+                children
+                    .spawn((Name::new("Saved Decks Row"), row()))
+                    .with_children(|children| {
+                        for (index, deck) in
+                            builder.saved.iter().enumerate().take(MAX_SAVED_DECKS_SHOWN)
+                        {
+                            children
+                                .button(&deck.name)
+                                .insert(DeckBuilderAction::Load(index));
+                        }
+                    });
+            }
+
+            children
+                .spawn((Name::new("Actions Row"), row()))
+                .with_children(|children| {
+                    children
+                        .button(locale.get("common-back"))
+                        .insert((DeckBuilderAction::Back, Localized("common-back")));
+                    children
+                        .button(locale.get("deck-standard"))
+                        .insert((DeckBuilderAction::UseStandard, Localized("deck-standard")));
+                    children
+                        .button(locale.get("deck-save"))
+                        .insert((DeckBuilderAction::Save, Localized("deck-save")));
+                    children
+                        .button(locale.get("deck-use"))
+                        .insert((DeckBuilderAction::Confirm, Localized("deck-use")));
+                });
+        });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
+}
+
+fn exit_deck_builder(mut commands: Commands) {
+    commands.remove_resource::<DeckBuilder>();
+    commands.trigger(PlaySoundtrack::Disable);
+}
+
+fn handle_deck_builder_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&DeckBuilderAction>,
+    mut builder: ResMut<DeckBuilder>,
+    mut match_settings: ResMut<MatchSettings>,
+    locale: Res<Locale>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        builder.editing_name = false;
+        builder.message.clear();
+        match *action {
+            DeckBuilderAction::EditName => builder.editing_name = true,
+            DeckBuilderAction::Remove(index) => {
+                let card_type = CARD_POOL[index].card_type;
+                let copies = builder.deck.copies(card_type);
+                builder.deck.set_copies(card_type, copies.saturating_sub(1));
+            }
+            DeckBuilderAction::Add(index) => {
+                let card_type = CARD_POOL[index].card_type;
+                let copies = builder.deck.copies(card_type);
+                builder.deck.set_copies(card_type, copies + 1);
+            }
+            DeckBuilderAction::Load(index) => {
+                if let Some(deck) = builder.saved.get(index).cloned() {
+                    builder.deck = deck;
+                }
+            }
+            DeckBuilderAction::Save => {
+                builder.message = match builder.deck.save() {
+                    Ok(path) => locale.format("deck-saved", &[("path", path)]),
+                    Err(error) => locale.format("deck-save-failed", &[("error", error)]),
+                };
+            }
+            DeckBuilderAction::UseStandard => {
+                match_settings.players[builder.seat - 1].deck = None;
+                next_screen.set(Screen::Setup);
+            }
+            DeckBuilderAction::Confirm => {
+                if builder.deck.is_legal() {
+                    match_settings.players[builder.seat - 1].deck = Some(builder.deck.clone());
+                    next_screen.set(Screen::Setup);
+                } else {
+                    builder.message = locale.get("deck-fix-first");
+                }
+            }
+            DeckBuilderAction::Back => next_screen.set(Screen::Setup),
+            DeckBuilderAction::LabelCopies(_)
+            | DeckBuilderAction::LabelSummary
+            | DeckBuilderAction::LabelProblems => {}
+        }
+    }
+}
+
+fn handle_name_input(
+    mut er_keyboard: EventReader<KeyboardInput>,
+    mut builder: ResMut<DeckBuilder>,
+) {
+    for event in er_keyboard.read() {
+        if event.state != ButtonState::Pressed || !builder.editing_name {
+            continue;
+        }
+
+        let typed = match &event.logical_key {
+            Key::Enter | Key::Escape => {
+                builder.editing_name = false;
+                continue;
+            }
+            Key::Backspace => {
+                builder.deck.name.pop();
+                continue;
+            }
+            Key::Space => " ".to_string(),
+            Key::Character(input) => input.to_string(),
+            _ => continue,
+        };
+        let name = &mut builder.deck.name;
+        for c in typed.chars().filter(|c| !c.is_control()) {
+            if name.chars().count() < DECK_NAME_MAX_LENGTH {
+                name.push(c);
+            }
+        }
+    }
+}
+
+fn update_deck_builder_labels(
+    builder: Res<DeckBuilder>,
+    locale: Res<Locale>,
+    action_query: Query<(&DeckBuilderAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+    spawned_query: Query<(), Added<DeckBuilderAction>>,
+) {
+    if !builder.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+
+    for (action, children) in &action_query {
+        let Some(&child) = children.iter().find(|child| text_query.contains(**child)) else {
+            continue;
+        };
+        let mut text = text_query.get_mut(child).unwrap();
+
+        match *action {
+            DeckBuilderAction::EditName => {
+                let cursor = if builder.editing_name { "_" } else { "" };
+                text.sections[0].value = locale.format(
+                    "deck-name",
+                    &[("name", format!("{}{}", builder.deck.name, cursor))],
+                );
+            }
+            DeckBuilderAction::LabelCopies(index) => {
+                text.sections[0].value =
+                    builder.deck.copies(CARD_POOL[index].card_type).to_string();
+            }
+            DeckBuilderAction::LabelSummary => {
+                text.sections[0].value = locale.format(
+                    "deck-summary",
+                    &[
+                        ("cards", builder.deck.size().to_string()),
+                        ("cost", builder.deck.cost().to_string()),
+                        ("budget", DECK_BUDGET.to_string()),
+                    ],
+                );
+            }
+            DeckBuilderAction::LabelProblems => {
+                let problems = builder.deck.problems();
+                text.sections[0].value = if !builder.message.is_empty() {
+                    builder.message.clone()
+                } else if problems.is_empty() {
+                    locale.get("deck-ready")
+                } else {
+                    problems
+                        .iter()
+                        .map(|problem| problem.describe(&locale))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                };
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! The draft screen where the seats pick their decks in turn before the
+//! first turn.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        cards::GameState,
+        draft::{Draft, ROUNDS},
+        locale::{Locale, Localized},
+        settings::{GameRng, MatchSettings},
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Draft), enter_draft);
+    app.add_systems(OnExit(Screen::Draft), exit_draft);
+
+    app.register_type::<DraftAction>();
+    app.add_systems(
+        Update,
+        (handle_draft_action, pick_for_ai, finish_draft, spawn_pack)
+            .chain()
+            .run_if(in_state(Screen::Draft).and_then(resource_exists::<Draft>)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum DraftAction {
+    Pick(usize),
+}
+
+/// Container rebuilt after every pick.
+#[derive(Component)]
+struct PackView;
+
+fn enter_draft(mut commands: Commands, locale: Res<Locale>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Draft))
+        .with_children(|children| {
+            children
+                .header(locale.get("draft-header"))
+                .insert(Localized("draft-header"));
+            children.spawn((
+                Name::new("Pack"),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                PackView,
+            ));
+        });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
+}
+
+fn exit_draft(mut commands: Commands) {
+    commands.remove_resource::<Draft>();
+    commands.trigger(PlaySoundtrack::Disable);
+}
+
+fn handle_draft_action(
+    mut button_query: InteractionQuery<&DraftAction>,
+    mut draft: ResMut<Draft>,
+    mut rng: ResMut<GameRng>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        match *action {
+            DraftAction::Pick(index) => draft.pick(index, &mut rng.0),
+        }
+    }
+}
+
+/// Computer seats pick right away, one card per frame.
+fn pick_for_ai(
+    mut draft: ResMut<Draft>,
+    match_settings: Res<MatchSettings>,
+    mut rng: ResMut<GameRng>,
+) {
+    if draft.is_finished() || !match_settings.is_ai(draft.picker()) {
+        return;
+    }
+    let index = draft.ai_pick();
+    draft.pick(index, &mut rng.0);
+}
+
+/// Hands every seat the deck it drafted and starts the match.
+fn finish_draft(
+    draft: Res<Draft>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut game_state: ResMut<GameState>,
+    mut next_screen: ResMut<NextState<Screen>>,
+) {
+    if !draft.is_finished() {
+        return;
+    }
+    for player in 1..=match_settings.num_players() {
+        match_settings.players[player - 1].deck = Some(draft.deck(player));
+    }
+    *game_state = GameState::from_settings(&match_settings);
+    next_screen.set(Screen::Playing);
+}
+
+/// Shows the picker the pack in front of them and what they kept so far.
+fn spawn_pack(
+    mut commands: Commands,
+    draft: Res<Draft>,
+    match_settings: Res<MatchSettings>,
+    locale: Res<Locale>,
+    view_query: Query<Entity, With<PackView>>,
+    spawned_query: Query<(), Added<PackView>>,
+) {
+    if (!draft.is_changed() && spawned_query.is_empty()) || draft.is_finished() {
+        return;
+    }
+
+    let player = draft.picker();
+    for view in &view_query {
+        commands
+            .entity(view)
+            .despawn_descendants()
+            .with_children(|children| {
+                children.label(locale.format(
+                    "draft-round",
+                    &[
+                        ("round", draft.round().to_string()),
+                        ("rounds", ROUNDS.to_string()),
+                        ("name", match_settings.player(player).name.clone()),
+                    ],
+                ));
+
+                children
+                    .spawn((
+                        Name::new("Pack Row"),
+                        NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Row,
+                                column_gap: Val::Px(10.0),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                    ))
+                    .with_children(|children| {
+                        for (index, card_type) in draft.pack(player).iter().enumerate() {
+                            children
+                                .button(locale.card_name(*card_type))
+                                .insert(DraftAction::Pick(index));
+                        }
+                    });
+
+                let picks = draft.deck(player);
+                let kept = picks
+                    .cards
+                    .iter()
+                    .map(|(card_type, copies)| {
+                        format!("{} x{}", locale.card_name(*card_type), copies)
+                    })
+                    .collect::<Vec<_>>();
+                if !kept.is_empty() {
+                    children.label(locale.format("draft-kept", &[("cards", kept.join(", "))]));
+                }
+            });
+    }
+}
@@ -5,7 +5,11 @@ use bevy::prelude::*;
 
 use super::Screen;
 use crate::{
-    game::assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+    game::{
+        assets::{HandleMap, ImageKey, SfxKey, SoundtrackKey},
+        campaign::CampaignHandle,
+        locale::{Locale, Localized},
+    },
     ui::prelude::*,
 };
 
@@ -17,12 +21,14 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
-fn enter_loading(mut commands: Commands) {
+fn enter_loading(mut commands: Commands, locale: Res<Locale>) {
     commands
         .ui_root()
         .insert(StateScoped(Screen::Loading))
         .with_children(|children| {
-            children.label("Loading...");
+            children
+                .label(locale.get("loading"))
+                .insert(Localized("loading"));
         });
 }
 
@@ -31,10 +37,13 @@ fn all_assets_loaded(
     image_handles: Res<HandleMap<ImageKey>>,
     sfx_handles: Res<HandleMap<SfxKey>>,
     soundtrack_handles: Res<HandleMap<SoundtrackKey>>,
+    campaign_handle: Res<CampaignHandle>,
 ) -> bool {
     image_handles.all_loaded(&asset_server)
         && sfx_handles.all_loaded(&asset_server)
         && soundtrack_handles.all_loaded(&asset_server)
+        && asset_server.is_loaded_with_dependencies(&campaign_handle.campaign)
+        && asset_server.is_loaded_with_dependencies(&campaign_handle.tutorial)
 }
 
 fn continue_to_title(mut next_screen: ResMut<NextState<Screen>>) {
@@ -0,0 +1,153 @@
+//! The lobby screen where an online match waits for every seat to be taken.
+//! Once the table is full, joining makes you a spectator.
+//!
+//! The server address is read from `CARTEL_SERVER` and defaults to
+//! [`DEFAULT_ADDRESS`].
+
+use bevy::prelude::*;
+use bevy_la_mesa::LaMesaPluginSettings;
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        campaign::ActiveScenario,
+        cards::GameState,
+        locale::{Locale, Localized},
+        settings::{Controller, GameRng, MatchSettings},
+        spawn::level::Seat,
+        spectator::Spectator,
+        tutorial::Tutorial,
+    },
+    net::{client::NetClient, protocol::ServerMessage, DEFAULT_ADDRESS},
+    ui::prelude::*,
+    GameCamera,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Lobby), enter_lobby);
+    app.add_systems(OnExit(Screen::Lobby), exit_lobby);
+
+    app.register_type::<LobbyAction>();
+    app.add_systems(
+        Update,
+        (
+            handle_lobby_action,
+            wait_for_match.run_if(resource_exists::<NetClient>),
+        )
+            .run_if(in_state(Screen::Lobby)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum LobbyAction {
+    Back,
+}
+
+fn enter_lobby(mut commands: Commands, match_settings: Res<MatchSettings>, locale: Res<Locale>) {
+    let address = std::env::var("CARTEL_SERVER").unwrap_or_else(|_| DEFAULT_ADDRESS.to_string());
+    let status = match NetClient::connect(&address, &match_settings.player(1).name) {
+        Ok(client) => {
+            commands.insert_resource(client);
+            locale.format("lobby-connected", &[("address", address)])
+        }
+        Err(error) => locale.format(
+            "lobby-connect-failed",
+            &[("address", address), ("error", error.to_string())],
+        ),
+    };
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Lobby))
+        .with_children(|children| {
+            children
+                .header(locale.get("lobby-header"))
+                .insert(Localized("lobby-header"));
+            children.label(status);
+            children
+                .button(locale.get("common-back"))
+                .insert((LobbyAction::Back, Localized("common-back")));
+        });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
+}
+
+fn exit_lobby(mut commands: Commands) {
+    commands.trigger(PlaySoundtrack::Disable);
+}
+
+fn handle_lobby_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&LobbyAction>,
+) {
+    for (interaction, action) in &mut button_query {
+        if matches!(interaction, Interaction::Pressed) {
+            match action {
+                LobbyAction::Back => {
+                    commands.remove_resource::<NetClient>();
+                    next_screen.set(Screen::Title);
+                }
+            }
+        }
+    }
+}
+
+fn wait_for_match(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut net: ResMut<NetClient>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut plugin_settings: ResMut<LaMesaPluginSettings>,
+    mut game_state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
+    mut camera_query: Query<&mut Transform, With<GameCamera>>,
+) {
+    let mut messages = net.receive().into_iter();
+    for message in messages.by_ref() {
+        // Spectators get no seat and never need the seed.
+        let (names, seat) = match message {
+            ServerMessage::Welcome {
+                player,
+                names,
+                hand_size,
+                seed,
+            } => (names, Some((player, hand_size, seed))),
+            ServerMessage::Spectating { names } => (names, None),
+            _ => continue,
+        };
+
+        // Every seat is human; the other ones are just played elsewhere.
+        match_settings.set_num_players(names.len());
+        for (settings, name) in match_settings.players.iter_mut().zip(names) {
+            settings.name = name;
+            settings.controller = Controller::Human;
+        }
+        plugin_settings.num_players = match_settings.num_players();
+
+        if let Some((player, hand_size, seed)) = seat {
+            match_settings.seed = seed;
+            plugin_settings.hand_size = hand_size;
+            rng.0 = StdRng::seed_from_u64(seed);
+            net.set_seat(player, hand_size);
+            for mut transform in &mut camera_query {
+                *transform = Seat(player).camera_transform();
+            }
+            commands.remove_resource::<Spectator>();
+        } else {
+            commands.init_resource::<Spectator>();
+        }
+        *game_state = GameState::from_settings(&match_settings);
+
+        commands.remove_resource::<ActiveScenario>();
+        commands.remove_resource::<Tutorial>();
+        next_screen.set(Screen::Playing);
+        break;
+    }
+    // Whatever came after the welcome belongs to the match.
+    net.requeue(messages.collect());
+}
@@ -1,8 +1,14 @@
 //! The game's main screen states and transitions between them.
 
+mod campaign;
 mod credits;
+mod deck_builder;
+mod draft;
 mod loading;
+mod lobby;
+mod mods;
 mod playing;
+mod setup;
 mod splash;
 mod title;
 
@@ -16,6 +22,12 @@ pub(super) fn plugin(app: &mut App) {
         splash::plugin,
         loading::plugin,
         title::plugin,
+        setup::plugin,
+        deck_builder::plugin,
+        draft::plugin,
+        campaign::plugin,
+        lobby::plugin,
+        mods::plugin,
         credits::plugin,
         playing::plugin,
     ));
@@ -28,6 +40,12 @@ pub enum Screen {
     Splash,
     Loading,
     Title,
+    Setup,
+    DeckBuilder,
+    Draft,
+    Campaign,
+    Lobby,
+    Mods,
     Credits,
     Playing,
 }
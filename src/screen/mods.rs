@@ -0,0 +1,168 @@
+//! The mod manager screen: switch mods on or off and set their priority.
+
+use bevy::prelude::*;
+
+use super::Screen;
+use crate::{
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        locale::{Locale, Localized},
+        mods::Mods,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Mods), enter_mods);
+    app.add_systems(OnExit(Screen::Mods), exit_mods);
+
+    app.register_type::<ModsAction>();
+    app.add_systems(
+        Update,
+        (handle_mods_action, spawn_mod_list)
+            .chain()
+            .run_if(in_state(Screen::Mods)),
+    );
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum ModsAction {
+    Toggle(usize),
+    Raise(usize),
+    Lower(usize),
+    Back,
+}
+
+/// Container rebuilt whenever the mods change.
+#[derive(Component)]
+struct ModList;
+
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn enter_mods(mut commands: Commands, locale: Res<Locale>) {
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Mods))
+        .with_children(|children| {
+            children
+                .header(locale.get("mods-header"))
+                .insert(Localized("mods-header"));
+            children.spawn((
+                Name::new("Mod List"),
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        row_gap: Val::Px(6.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ModList,
+            ));
+            children
+                .button(locale.get("common-back"))
+                .insert((ModsAction::Back, Localized("common-back")));
+        });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
+}
+
+fn exit_mods(mut commands: Commands) {
+    commands.trigger(PlaySoundtrack::Disable);
+}
+
+fn handle_mods_action(
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&ModsAction>,
+    mut mods: ResMut<Mods>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        match *action {
+            ModsAction::Toggle(index) => mods.toggle(index),
+            ModsAction::Raise(index) => mods.raise(index),
+            ModsAction::Lower(index) => mods.lower(index),
+            ModsAction::Back => next_screen.set(Screen::Title),
+        }
+    }
+}
+
+/// Lists the mods from the highest priority to the lowest, with the errors
+/// keeping broken ones off and the overrides between enabled ones.
+fn spawn_mod_list(
+    mut commands: Commands,
+    mods: Res<Mods>,
+    locale: Res<Locale>,
+    list_query: Query<Entity, With<ModList>>,
+    spawned_query: Query<(), Added<ModList>>,
+) {
+    if !mods.is_changed() && spawned_query.is_empty() {
+        return;
+    }
+
+    for list in &list_query {
+        commands
+            .entity(list)
+            .despawn_descendants()
+            .with_children(|children| {
+                if mods.installed.is_empty() {
+                    children.label(locale.get("mods-none"));
+                }
+
+                for (index, installed) in mods.installed.iter().enumerate() {
+                    children
+                        .spawn((Name::new(format!("Mod Row {}", index)), row()))
+                        .with_children(|children| {
+                            children.label(format!("{}. {}", index + 1, installed.name()));
+                            let toggle = match (installed.is_valid(), installed.enabled) {
+                                (false, _) => "mods-broken",
+                                (true, true) => "mods-on",
+                                (true, false) => "mods-off",
+                            };
+                            children
+                                .button(locale.get(toggle))
+                                .insert(ModsAction::Toggle(index));
+                            children
+                                .button(locale.get("mods-up"))
+                                .insert(ModsAction::Raise(index));
+                            children
+                                .button(locale.get("mods-down"))
+                                .insert(ModsAction::Lower(index));
+                        });
+
+                    if let Some(manifest) = &installed.manifest {
+                        if !manifest.description.is_empty() {
+                            children.label(&manifest.description);
+                        }
+                    }
+                    for error in &installed.errors {
+                        children.label(locale.format("mods-error", &[("error", error.clone())]));
+                    }
+                }
+
+                let conflicts = mods.conflicts();
+                if !conflicts.is_empty() {
+                    children.label(locale.get("mods-overridden"));
+                    for conflict in conflicts {
+                        children.label(conflict);
+                    }
+                }
+            });
+    }
+}
@@ -0,0 +1,395 @@
+//! The new-game setup screen where the host configures the match.
+
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+use bevy_la_mesa::LaMesaPluginSettings;
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::{deck_builder::DeckBuilder, Screen};
+use crate::{
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        campaign::ActiveScenario,
+        cards::GameState,
+        draft::Draft,
+        locale::{Locale, Localized},
+        mods::Mods,
+        settings::{
+            Controller, GameRng, MatchSettings, RulesPreset, DEFAULT_SPOILAGE_TURNS, MAX_PLAYERS,
+            PLAYER_COLORS,
+        },
+        spectator::Spectator,
+        tutorial::Tutorial,
+    },
+    ui::prelude::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Setup), enter_setup);
+    app.add_systems(OnExit(Screen::Setup), exit_setup);
+
+    app.register_type::<SetupAction>();
+    app.init_resource::<SetupFocus>();
+    app.add_systems(
+        Update,
+        (handle_setup_action, handle_text_input, update_setup_labels)
+            .chain()
+            .run_if(in_state(Screen::Setup)),
+    );
+}
+
+const NAME_MAX_LENGTH: usize = 16;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
+#[reflect(Component)]
+enum SetupAction {
+    PlayerCountDown,
+    PlayerCountUp,
+    LabelPlayerCount,
+    EditName(usize),
+    CycleColor(usize),
+    ToggleController(usize),
+    BuildDeck(usize),
+    CyclePreset,
+    ToggleSpoilage,
+    ToggleDraft,
+    EditSeed,
+    RandomSeed,
+    Start,
+    Back,
+}
+
+/// Row holding the settings of one seat; hidden when the seat is unused.
+#[derive(Component)]
+struct SeatRow(usize);
+
+/// Field currently receiving keyboard input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SetupField {
+    Name(usize),
+    Seed,
+}
+
+#[derive(Resource, Default)]
+struct SetupFocus(Option<SetupField>);
+
+fn row() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn enter_setup(mut commands: Commands, mut focus: ResMut<SetupFocus>, locale: Res<Locale>) {
+    focus.0 = None;
+
+    commands
+        .ui_root()
+        .insert(StateScoped(Screen::Setup))
+        .with_children(|children| {
+            children
+                .header(locale.get("setup-header"))
+                .insert(Localized("setup-header"));
+
+            children
+                .spawn((Name::new("Players Row"), row()))
+                .with_children(|children| {
+                    children.button("-").insert(SetupAction::PlayerCountDown);
+                    children
+                        .label("Players: 2")
+                        .insert(SetupAction::LabelPlayerCount);
+                    children.button("+").insert(SetupAction::PlayerCountUp);
+                });
+
+            for seat in 1..=MAX_PLAYERS {
+                children
+                    .spawn((
+                        Name::new(format!("Seat Row {}", seat)),
+                        row(),
+                        SeatRow(seat),
+                    ))
+                    .with_children(|children| {
+                        children.button("Name").insert(SetupAction::EditName(seat));
+                        children
+                            .button(locale.get("setup-color"))
+                            .insert((SetupAction::CycleColor(seat), Localized("setup-color")));
+                        children
+                            .button("Human")
+                            .insert(SetupAction::ToggleController(seat));
+                        children.button("Deck").insert(SetupAction::BuildDeck(seat));
+                    });
+            }
+
+            children
+                .spawn((Name::new("Rules Row"), row()))
+                .with_children(|children| {
+                    children.button("Rules").insert(SetupAction::CyclePreset);
+                    children
+                        .button("Spoilage")
+                        .insert(SetupAction::ToggleSpoilage);
+                    children.button("Draft").insert(SetupAction::ToggleDraft);
+                    children.button("Seed").insert(SetupAction::EditSeed);
+                    children
+                        .button(locale.get("setup-random-seed"))
+                        .insert((SetupAction::RandomSeed, Localized("setup-random-seed")));
+                });
+
+            children
+                .spawn((Name::new("Actions Row"), row()))
+                .with_children(|children| {
+                    children
+                        .button(locale.get("common-back"))
+                        .insert((SetupAction::Back, Localized("common-back")));
+                    children
+                        .button(locale.get("setup-start"))
+                        .insert((SetupAction::Start, Localized("setup-start")));
+                });
+        });
+
+    commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
+}
+
+fn exit_setup(mut commands: Commands) {
+    commands.trigger(PlaySoundtrack::Disable);
+}
+
+fn handle_setup_action(
+    mut commands: Commands,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut button_query: InteractionQuery<&SetupAction>,
+    mut match_settings: ResMut<MatchSettings>,
+    mut focus: ResMut<SetupFocus>,
+    mut plugin_settings: ResMut<LaMesaPluginSettings>,
+    mut game_state: ResMut<GameState>,
+    mut rng: ResMut<GameRng>,
+    mods: Res<Mods>,
+) {
+    for (interaction, action) in &mut button_query {
+        if !matches!(interaction, Interaction::Pressed) {
+            continue;
+        }
+
+        focus.0 = None;
+        match *action {
+            SetupAction::PlayerCountDown => {
+                let num_players = match_settings.num_players() - 1;
+                match_settings.set_num_players(num_players);
+            }
+            SetupAction::PlayerCountUp => {
+                let num_players = match_settings.num_players() + 1;
+                match_settings.set_num_players(num_players);
+            }
+            SetupAction::EditName(seat) => focus.0 = Some(SetupField::Name(seat)),
+            SetupAction::CycleColor(seat) => {
+                let player = &mut match_settings.players[seat - 1];
+                let index = PLAYER_COLORS
+                    .iter()
+                    .position(|color| *color == player.color)
+                    .map_or(0, |index| (index + 1) % PLAYER_COLORS.len());
+                player.color = PLAYER_COLORS[index];
+            }
+            SetupAction::ToggleController(seat) => {
+                let player = &mut match_settings.players[seat - 1];
+                player.controller = match player.controller {
+                    Controller::Human => Controller::Ai,
+                    Controller::Ai => Controller::Human,
+                };
+            }
+            SetupAction::BuildDeck(seat) => {
+                let deck = match_settings.player(seat).deck.clone();
+                commands.insert_resource(DeckBuilder::new(seat, deck));
+                next_screen.set(Screen::DeckBuilder);
+            }
+            SetupAction::CyclePreset => {
+                match_settings.preset = mods.next_preset(match_settings.preset);
+            }
+            SetupAction::ToggleSpoilage => {
+                match_settings.spoilage_turns = match match_settings.spoilage_turns {
+                    Some(_) => None,
+                    None => Some(DEFAULT_SPOILAGE_TURNS),
+                };
+            }
+            SetupAction::ToggleDraft => match_settings.draft = !match_settings.draft,
+            SetupAction::EditSeed => focus.0 = Some(SetupField::Seed),
+            SetupAction::RandomSeed => match_settings.seed = rand::random::<u32>() as u64,
+            SetupAction::Start => {
+                // Everything the board and the rules read on `SpawnBoard` has
+                // to be in place before entering `Screen::Playing`.
+                plugin_settings.num_players = match_settings.num_players();
+                plugin_settings.hand_size = match_settings.preset.hand_size();
+                *game_state = GameState::from_settings(&match_settings);
+                rng.0 = StdRng::seed_from_u64(match_settings.seed);
+                commands.remove_resource::<ActiveScenario>();
+                commands.remove_resource::<Tutorial>();
+                // Nobody to play for: watch the computer play itself.
+                if (1..=match_settings.num_players()).all(|player| match_settings.is_ai(player)) {
+                    commands.init_resource::<Spectator>();
+                } else {
+                    commands.remove_resource::<Spectator>();
+                }
+                if match_settings.draft {
+                    let draft = Draft::new(match_settings.num_players(), &mut rng.0);
+                    commands.insert_resource(draft);
+                    next_screen.set(Screen::Draft);
+                } else {
+                    next_screen.set(Screen::Playing);
+                }
+            }
+            SetupAction::Back => next_screen.set(Screen::Title),
+            SetupAction::LabelPlayerCount => {}
+        }
+    }
+}
+
+fn handle_text_input(
+    mut er_keyboard: EventReader<KeyboardInput>,
+    mut focus: ResMut<SetupFocus>,
+    mut match_settings: ResMut<MatchSettings>,
+) {
+    for event in er_keyboard.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let Some(field) = focus.0 else {
+            continue;
+        };
+
+        match (&event.logical_key, field) {
+            (Key::Enter | Key::Escape, _) => focus.0 = None,
+            (Key::Backspace, SetupField::Name(seat)) => {
+                match_settings.players[seat - 1].name.pop();
+            }
+            (Key::Backspace, SetupField::Seed) => match_settings.seed /= 10,
+            (Key::Space, SetupField::Name(seat)) => {
+                let name = &mut match_settings.players[seat - 1].name;
+                if name.chars().count() < NAME_MAX_LENGTH {
+                    name.push(' ');
+                }
+            }
+            (Key::Character(input), SetupField::Name(seat)) => {
+                let name = &mut match_settings.players[seat - 1].name;
+                for c in input.chars().filter(|c| !c.is_control()) {
+                    if name.chars().count() < NAME_MAX_LENGTH {
+                        name.push(c);
+                    }
+                }
+            }
+            (Key::Character(input), SetupField::Seed) => {
+                for digit in input.chars().filter_map(|c| c.to_digit(10)) {
+                    if let Some(seed) = match_settings
+                        .seed
+                        .checked_mul(10)
+                        .and_then(|seed| seed.checked_add(digit as u64))
+                    {
+                        match_settings.seed = seed;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_setup_labels(
+    match_settings: Res<MatchSettings>,
+    mods: Res<Mods>,
+    locale: Res<Locale>,
+    focus: Res<SetupFocus>,
+    action_query: Query<(&SetupAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+    mut row_query: Query<(&SeatRow, &mut Style)>,
+    spawned_query: Query<(), Added<SetupAction>>,
+) {
+    if !match_settings.is_changed()
+        && !focus.is_changed()
+        && !locale.is_changed()
+        && spawned_query.is_empty()
+    {
+        return;
+    }
+
+    let cursor = |field: SetupField| if focus.0 == Some(field) { "_" } else { "" };
+
+    for (seat_row, mut style) in &mut row_query {
+        style.display = if seat_row.0 <= match_settings.num_players() {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+
+    for (action, children) in &action_query {
+        let Some(&child) = children.iter().find(|child| text_query.contains(**child)) else {
+            continue;
+        };
+        let mut text = text_query.get_mut(child).unwrap();
+
+        match *action {
+            SetupAction::LabelPlayerCount => {
+                text.sections[0].value = locale.format(
+                    "setup-players",
+                    &[("count", match_settings.num_players().to_string())],
+                );
+            }
+            SetupAction::EditName(seat) if seat <= match_settings.num_players() => {
+                text.sections[0].value = format!(
+                    "{}{}",
+                    match_settings.player(seat).name,
+                    cursor(SetupField::Name(seat))
+                );
+            }
+            SetupAction::CycleColor(seat) if seat <= match_settings.num_players() => {
+                text.sections[0].style.color = match_settings.player(seat).color;
+            }
+            SetupAction::ToggleController(seat) if seat <= match_settings.num_players() => {
+                text.sections[0].value = match match_settings.player(seat).controller {
+                    Controller::Human => locale.get("setup-human"),
+                    Controller::Ai => locale.get("setup-ai"),
+                };
+            }
+            SetupAction::BuildDeck(seat) if seat <= match_settings.num_players() => {
+                let name = match &match_settings.player(seat).deck {
+                    Some(deck) => deck.name.clone(),
+                    None => locale.get("setup-deck-standard"),
+                };
+                text.sections[0].value = locale.format("setup-deck", &[("name", name)]);
+            }
+            SetupAction::CyclePreset => {
+                let preset = match match_settings.preset {
+                    RulesPreset::Custom(_) => mods.preset_name(match_settings.preset),
+                    preset => locale.name_of("preset", preset),
+                };
+                text.sections[0].value = locale.format("setup-rules", &[("preset", preset)]);
+            }
+            SetupAction::ToggleSpoilage => {
+                text.sections[0].value = match match_settings.spoilage_turns {
+                    Some(turns) => locale.format("setup-spoilage", &[("turns", turns.to_string())]),
+                    None => locale.get("setup-spoilage-off"),
+                };
+            }
+            SetupAction::ToggleDraft => {
+                text.sections[0].value = if match_settings.draft {
+                    locale.get("setup-draft-on")
+                } else {
+                    locale.get("setup-draft-off")
+                };
+            }
+            SetupAction::EditSeed => {
+                let seed = format!("{}{}", match_settings.seed, cursor(SetupField::Seed));
+                text.sections[0].value = locale.format("setup-seed", &[("seed", seed)]);
+            }
+            _ => {}
+        }
+    }
+}
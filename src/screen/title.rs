@@ -7,7 +7,11 @@ use bevy::{
 
 use super::Screen;
 use crate::{
-    game::{assets::SoundtrackKey, audio::soundtrack::PlaySoundtrack},
+    game::{
+        assets::SoundtrackKey,
+        audio::soundtrack::PlaySoundtrack,
+        locale::{Locale, Localized},
+    },
     ui::prelude::*,
 };
 
@@ -16,14 +20,25 @@ pub(super) fn plugin(app: &mut App) {
     app.add_systems(OnExit(Screen::Title), exit_title);
 
     app.register_type::<TitleAction>();
-    app.add_systems(Update, handle_title_action.run_if(in_state(Screen::Title)));
+    app.add_systems(
+        Update,
+        (handle_title_action, update_language_label).run_if(in_state(Screen::Title)),
+    );
 }
 
 #[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Reflect)]
 #[reflect(Component)]
 enum TitleAction {
     Play,
+    Campaign,
+    /// Online play needs sockets and threads, which the web build lacks.
+    #[cfg(not(target_family = "wasm"))]
+    Online,
+    /// Mods are read from a folder, which the web build lacks.
+    #[cfg(not(target_family = "wasm"))]
+    Mods,
     Credits,
+    Language,
     /// Exit doesn't work well with embedded applications.
     #[cfg(not(target_family = "wasm"))]
     Exit,
@@ -31,7 +46,7 @@ enum TitleAction {
 
 const TITLE_BACKGROUND_COLOR: Color = Color::srgb(0.0, 0.0, 239.0);
 
-fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>, locale: Res<Locale>) {
     commands
         .ui_root()
         .insert((
@@ -78,11 +93,31 @@ fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>) {
         ))
         .insert(StateScoped(Screen::Title))
         .with_children(|children| {
-            children.button("Play").insert(TitleAction::Play);
-            children.button("Credits").insert(TitleAction::Credits);
+            children
+                .button(locale.get("title-play"))
+                .insert((TitleAction::Play, Localized("title-play")));
+            children
+                .button(locale.get("title-campaign"))
+                .insert((TitleAction::Campaign, Localized("title-campaign")));
+            #[cfg(not(target_family = "wasm"))]
+            children
+                .button(locale.get("title-online"))
+                .insert((TitleAction::Online, Localized("title-online")));
+            #[cfg(not(target_family = "wasm"))]
+            children
+                .button(locale.get("title-mods"))
+                .insert((TitleAction::Mods, Localized("title-mods")));
+            children
+                .button(locale.get("title-credits"))
+                .insert((TitleAction::Credits, Localized("title-credits")));
+            children
+                .button(language_label(&locale))
+                .insert(TitleAction::Language);
 
             #[cfg(not(target_family = "wasm"))]
-            children.button("Exit").insert(TitleAction::Exit);
+            children
+                .button(locale.get("title-exit"))
+                .insert((TitleAction::Exit, Localized("title-exit")));
         });
 
     commands.trigger(PlaySoundtrack::Key(SoundtrackKey::Title));
@@ -91,13 +126,23 @@ fn enter_title(mut commands: Commands, asset_server: Res<AssetServer>) {
 fn handle_title_action(
     mut next_screen: ResMut<NextState<Screen>>,
     mut button_query: InteractionQuery<&TitleAction>,
+    mut locale: ResMut<Locale>,
     #[cfg(not(target_family = "wasm"))] mut app_exit: EventWriter<AppExit>,
 ) {
     for (interaction, action) in &mut button_query {
         if matches!(interaction, Interaction::Pressed) {
             match action {
-                TitleAction::Play => next_screen.set(Screen::Playing),
+                TitleAction::Play => next_screen.set(Screen::Setup),
+                TitleAction::Campaign => next_screen.set(Screen::Campaign),
+                #[cfg(not(target_family = "wasm"))]
+                TitleAction::Online => next_screen.set(Screen::Lobby),
+                #[cfg(not(target_family = "wasm"))]
+                TitleAction::Mods => next_screen.set(Screen::Mods),
                 TitleAction::Credits => next_screen.set(Screen::Credits),
+                TitleAction::Language => {
+                    let language = locale.language().next();
+                    locale.set_language(language);
+                }
 
                 #[cfg(not(target_family = "wasm"))]
                 TitleAction::Exit => {
@@ -108,6 +153,34 @@ fn handle_title_action(
     }
 }
 
+fn language_label(locale: &Locale) -> String {
+    format!(
+        "{}: {}",
+        locale.get("title-language"),
+        locale.language().native_name()
+    )
+}
+
+fn update_language_label(
+    locale: Res<Locale>,
+    button_query: Query<(&TitleAction, &Children)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !locale.is_changed() {
+        return;
+    }
+    for (action, children) in &button_query {
+        if *action != TitleAction::Language {
+            continue;
+        }
+        for &child in children {
+            if let Ok(mut text) = text_query.get_mut(child) {
+                text.sections[0].value = language_label(&locale);
+            }
+        }
+    }
+}
+
 fn exit_title(mut commands: Commands) {
     // We could use [`StateScoped`] on the sound playing entites instead.
     commands.trigger(PlaySoundtrack::Disable);